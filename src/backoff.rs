@@ -1,104 +1,2933 @@
-use std::time::Duration;
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use crate::Rng;
 
 /// The implementation of the algorithm used to time when failures should he
 /// retried.
 pub trait Backoff {
     /// The duration that must be waited until the function is tried again.
     fn backoff_period(&mut self, iterations: u32) -> Duration;
+
+    /// The duration that must be waited until the function is tried again,
+    /// given the full [BackoffContext](crate::backoff::BackoffContext) of the
+    /// retry operation.
+    ///
+    /// By default it calls
+    /// [backoff_period](crate::backoff::Backoff::backoff_period). Strategies
+    /// that depend on the previous delay or the elapsed time can override it
+    /// instead of tracking that state themselves.
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        self.backoff_period(ctx.iterations)
+    }
+
+    /// The duration that must be waited until the function is tried again, or
+    /// `None` if the function shouldn't be retried anymore.
+    ///
+    /// This is what the retry functions call, by default it calls
+    /// [compute](crate::backoff::Backoff::compute) and never gives up.
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        Some(self.compute(ctx))
+    }
+
+    /// Forgets any state kept from a previous retry operation. The retry
+    /// functions call it before the first attempt, so a backoff reused across
+    /// operations through a mutable reference starts fresh every time.
+    ///
+    /// By default it does nothing, wrappers reset the backoffs they wrap.
+    fn reset(&mut self) {}
+}
+
+impl<B: Backoff + ?Sized> Backoff for &mut B {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        (**self).backoff_period(iterations)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        (**self).compute(ctx)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        (**self).next_backoff(ctx)
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+impl<B: Backoff + ?Sized> Backoff for Box<B> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        (**self).backoff_period(iterations)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        (**self).compute(ctx)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        (**self).next_backoff(ctx)
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+/// Combinators available on every [Backoff](crate::backoff::Backoff), each
+/// returning a wrapper that is itself a [Backoff](crate::backoff::Backoff).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// let mut backoff = ExponentialBackoff::with_base(2.0)
+///     .scale(2.0)
+///     .plus(Duration::from_millis(50))
+///     .map(|delay, _| delay.min(Duration::from_secs(1)));
+///
+/// assert_eq!(backoff.backoff_period(1), Duration::from_millis(250));
+/// assert_eq!(backoff.backoff_period(10), Duration::from_secs(1));
+/// ```
+pub trait BackoffExt: Backoff + Sized {
+    /// Transforms every delay with `map`, which is also passed the number of
+    /// iterations. See [MapBackoff](crate::backoff::MapBackoff).
+    fn map<F>(self, map: F) -> MapBackoff<Self, F>
+    where
+        F: FnMut(Duration, u32) -> Duration,
+    {
+        MapBackoff {
+            inner: self,
+            map,
+        }
+    }
+
+    /// Multiplies every delay by `factor`, see
+    /// [ScaledBackoff](crate::backoff::ScaledBackoff).
+    ///
+    /// # Panics
+    /// Panics if `factor` isn't a finite, non-negative number.
+    fn scale(self, factor: f64) -> ScaledBackoff<Self> {
+        ScaledBackoff::new(self, factor)
+    }
+
+    /// Adds `offset` to every delay, see
+    /// [PlusBackoff](crate::backoff::PlusBackoff).
+    fn plus(self, offset: Duration) -> PlusBackoff<Self> {
+        PlusBackoff {
+            inner: self,
+            offset,
+        }
+    }
+
+    /// Uses this backoff for the first `switch_after` iterations and `next`
+    /// afterwards, see [PhasedBackoff](crate::backoff::PhasedBackoff).
+    ///
+    /// # Example
+    /// ```
+    /// # use tryagain::*;
+    /// # use std::time::Duration;
+    /// // Retry immediately three times, then back off exponentially.
+    /// let mut backoff = ImmediateBackoff.then(3, ExponentialBackoff::with_base(2.0));
+    ///
+    /// assert_eq!(backoff.backoff_period(2), Duration::ZERO);
+    /// assert_eq!(backoff.backoff_period(4), Duration::from_millis(100));
+    /// ```
+    fn then<B: Backoff>(self, switch_after: u32, next: B) -> PhasedBackoff<Self, B> {
+        PhasedBackoff::new(self, switch_after, next)
+    }
+}
+
+impl<B: Backoff> BackoffExt for B {}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that transforms the
+/// delay of another backoff with a closure, created with
+/// [BackoffExt::map](crate::backoff::BackoffExt::map).
+#[derive(Debug, Clone)]
+pub struct MapBackoff<T: Backoff, F> {
+    inner: T,
+    map: F,
+}
+
+impl<T, F> Backoff for MapBackoff<T, F>
+where
+    T: Backoff,
+    F: FnMut(Duration, u32) -> Duration,
+{
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self.inner.backoff_period(iterations);
+        (self.map)(duration, iterations)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        let duration = self.inner.compute(ctx);
+        (self.map)(duration, ctx.iterations)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some((self.map)(duration, ctx.iterations))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that adds a fixed offset
+/// to the delay of another backoff, created with
+/// [BackoffExt::plus](crate::backoff::BackoffExt::plus).
+///
+/// Delays that are too large to be represented saturate at
+/// [Duration::MAX](core::time::Duration::MAX).
+#[derive(Debug, Clone)]
+pub struct PlusBackoff<T: Backoff> {
+    inner: T,
+    offset: Duration,
+}
+
+impl<T: Backoff> Backoff for PlusBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.inner.backoff_period(iterations).saturating_add(self.offset)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        self.inner.compute(ctx).saturating_add(self.offset)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(duration.saturating_add(self.offset))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that offsets the delay
+/// of another backoff by a deterministic share of a window, so that workers
+/// failing at the same time spread their retries across the window the same
+/// way on every run, without any randomness.
+///
+/// Equation: `delay = delay + window * index / total`
+///
+/// Delays that are too large to be represented saturate at
+/// [Duration::MAX](core::time::Duration::MAX).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// let window = Duration::from_secs(10);
+/// let mut backoff = StaggerBackoff::new(ConstantBackoff::from_millis(500), 3, 10, window);
+///
+/// assert_eq!(backoff.backoff_period(1), Duration::from_millis(3500));
+/// ```
+#[derive(Debug, Clone)]
+pub struct StaggerBackoff<T: Backoff> {
+    inner: T,
+    offset: Duration,
+}
+
+impl<T: Backoff> StaggerBackoff<T> {
+    /// Creates a [StaggerBackoff](crate::backoff::StaggerBackoff) for the
+    /// worker at `index` out of `total` workers.
+    ///
+    /// # Panics
+    /// Panics if `index` isn't less than `total`.
+    pub fn new(inner: T, index: u32, total: u32, window: Duration) -> Self {
+        assert!(index < total, "StaggerBackoff index must be less than the total");
+
+        let offset = window.as_nanos() * index as u128 / total as u128;
+        Self::with_offset(inner, duration_from_nanos(offset))
+    }
+
+    /// Creates a [StaggerBackoff](crate::backoff::StaggerBackoff) whose offset
+    /// within `window` is derived from hashing `key`, such as a host name.
+    ///
+    /// The key is hashed with FNV-1a rather than the standard library's
+    /// unspecified hasher, so the same key always results in the same offset,
+    /// across processes and Rust releases alike.
+    pub fn from_key<K: core::hash::Hash>(inner: T, key: &K, window: Duration) -> Self {
+        use core::hash::Hasher;
+
+        let mut hasher = Fnv1aHasher::default();
+        key.hash(&mut hasher);
+
+        // The upper 32 bits of the hash keep the multiplication from
+        // overflowing.
+        let fraction = hasher.finish() >> 32;
+        let offset = (window.as_nanos() * fraction as u128) >> 32;
+        Self::with_offset(inner, duration_from_nanos(offset))
+    }
+
+    fn with_offset(inner: T, offset: Duration) -> Self {
+        Self {
+            inner,
+            offset,
+        }
+    }
+
+    /// The offset added to every delay.
+    pub fn offset(&self) -> Duration {
+        self.offset
+    }
+}
+
+impl<T: Backoff> Backoff for StaggerBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.inner.backoff_period(iterations).saturating_add(self.offset)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        self.inner.compute(ctx).saturating_add(self.offset)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(duration.saturating_add(self.offset))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// Information about a retry operation passed to
+/// [compute](crate::backoff::Backoff::compute).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackoffContext {
+    /// The number of iterations, the same value passed to
+    /// [backoff_period](crate::backoff::Backoff::backoff_period).
+    pub iterations: u32,
+    /// The delay that was computed for the previous iteration, if any.
+    pub previous: Option<Duration>,
+    /// The time elapsed since the first attempt.
+    pub elapsed: Duration,
+}
+
+/// Builds the [BackoffContext](crate::backoff::BackoffContext) for each
+/// iteration of a retry loop.
+#[derive(Debug, Default)]
+pub(crate) struct Schedule {
+    previous: Option<Duration>,
+}
+
+impl Schedule {
+    /// Resets `backoff` and starts a new schedule for a retry operation.
+    pub(crate) fn start<B: Backoff>(backoff: &mut B) -> Self {
+        backoff.reset();
+        Self::default()
+    }
+
+    /// Computes the next delay from `backoff`, remembering it as the previous
+    /// delay for the next iteration. Returns `None` if the backoff gave up.
+    pub(crate) fn next<B: Backoff>(
+        &mut self,
+        backoff: &mut B,
+        iterations: u32,
+        elapsed: Duration,
+    ) -> Option<Duration> {
+        self.next_with(iterations, elapsed, |ctx| backoff.next_backoff(ctx))
+    }
+
+    /// The same as [next](Self::next) but the delay is computed by `next`,
+    /// for backoffs that need more than the context.
+    pub(crate) fn next_with(
+        &mut self,
+        iterations: u32,
+        elapsed: Duration,
+        next: impl FnOnce(&BackoffContext) -> Option<Duration>,
+    ) -> Option<Duration> {
+        let ctx = self.context(iterations, elapsed);
+
+        let duration = next(&ctx)?;
+        self.previous = Some(duration);
+        Some(duration)
+    }
+
+    /// The same as [next_with](Self::next_with) but the delay is computed
+    /// asynchronously by `next`.
+    #[cfg(any(
+        feature = "runtime-tokio",
+        feature = "runtime-async-std",
+        feature = "runtime-smol",
+        all(feature = "runtime-wasm", target_arch = "wasm32")
+    ))]
+    pub(crate) async fn next_async<Fut>(
+        &mut self,
+        iterations: u32,
+        elapsed: Duration,
+        next: impl FnOnce(BackoffContext) -> Fut,
+    ) -> Option<Duration>
+    where
+        Fut: core::future::Future<Output = Option<Duration>>,
+    {
+        let ctx = self.context(iterations, elapsed);
+
+        let duration = next(ctx).await?;
+        self.previous = Some(duration);
+        Some(duration)
+    }
+
+    /// The same as [next](Self::next) but for retry loops where the backoff
+    /// isn't allowed to give up.
+    #[cfg(feature = "std")]
+    pub(crate) fn compute<B: Backoff>(
+        &mut self,
+        backoff: &mut B,
+        iterations: u32,
+        elapsed: Duration,
+    ) -> Duration {
+        let ctx = self.context(iterations, elapsed);
+
+        let duration = backoff.compute(&ctx);
+        self.previous = Some(duration);
+        duration
+    }
+
+    fn context(&self, iterations: u32, elapsed: Duration) -> BackoffContext {
+        BackoffContext {
+            iterations,
+            previous: self.previous,
+            elapsed,
+        }
+    }
 }
 
 /// A [Backoff](crate::backoff::Backoff) implementation that exponentially
 /// increases the delay between attempts.
 ///
 /// # Details
-/// Currently [ExponentialBackoff](crate::backoff::ExponentialBackoff) uses the
-/// formula `delay = 100(base^iterations - 1)` measured in milliseconds.
+/// By default [ExponentialBackoff](crate::backoff::ExponentialBackoff) uses the
+/// formula `delay = 100(base^iterations - 1)` measured in milliseconds. When an
+/// initial interval is configured with
+/// [with_initial](crate::backoff::ExponentialBackoff::with_initial) the formula
+/// becomes `delay = initial * base^iterations`. In both cases the delay is capped
+/// by the interval configured with
+/// [with_max](crate::backoff::ExponentialBackoff::with_max), if any.
+///
+/// An [ExponentialBackoff](crate::backoff::ExponentialBackoff) created with
+/// [new](crate::backoff::ExponentialBackoff::new) has no delay for iteration
+/// `0` and uses `delay = initial * base^(iterations - 1)` afterwards, so the
+/// first non-zero delay is exactly `initial`.
+///
+/// The default formula results in no delay for iteration `0`. Enabling
+/// [first_attempt_delay](crate::backoff::ExponentialBackoff::first_attempt_delay)
+/// shifts it to `delay = 100(base^(iterations + 1) - 1)` so that the first
+/// retry already waits `100(base - 1)` milliseconds.
+///
+/// Delays that are too large to be represented saturate at
+/// [Duration::MAX](core::time::Duration::MAX) instead of panicking or
+/// wrapping around.
+///
+/// If a maximum elapsed time is configured with
+/// [with_max_elapsed](crate::backoff::ExponentialBackoff::with_max_elapsed) the
+/// backoff gives up once that much time has passed since the first attempt.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ExponentialBackoff {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_base"))]
+    base: f32,
+    initial: Option<Duration>,
+    initial_iteration: u32,
+    max: Option<Duration>,
+    max_elapsed: Option<Duration>,
+    first_attempt_delay: bool,
+    #[cfg(feature = "jitter")]
+    jitter: Option<f32>,
+    #[cfg(feature = "jitter")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "random_seed"))]
+    jitter_seed: u64,
+}
+
+#[cfg(all(feature = "jitter", feature = "serde"))]
+fn random_seed() -> u64 {
+    fastrand::u64(..)
+}
+
+fn validate_base(base: f32) -> Result<f32, InvalidBase> {
+    if base.is_finite() && base > 1.0 {
+        Ok(base)
+    } else {
+        Err(InvalidBase { base })
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_base<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let base = <f32 as serde::Deserialize>::deserialize(deserializer)?;
+    validate_base(base).map_err(serde::de::Error::custom)
+}
+
+/// The error returned by
+/// [try_with_base](crate::backoff::ExponentialBackoff::try_with_base) when the
+/// base isn't a finite number greater than `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidBase {
     base: f32,
 }
 
+impl InvalidBase {
+    /// The base that was rejected.
+    pub fn base(&self) -> f32 {
+        self.base
+    }
+}
+
+impl fmt::Display for InvalidBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ExponentialBackoff base must be a finite number greater than 1, got {}",
+            self.base
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidBase {}
+
 impl ExponentialBackoff {
+    /// Creates an [ExponentialBackoff](crate::backoff::ExponentialBackoff)
+    /// that waits `initial` after the first failure and multiplies the delay
+    /// by `base` after every following failure, up to `max`.
+    ///
+    /// Iteration `0` isn't delayed, the same as the default formula.
+    ///
+    /// Equation: `delay = min(max, initial * base^(iterations - 1))`
+    ///
+    /// # Example
+    /// ```
+    /// # use tryagain::*;
+    /// # use std::time::Duration;
+    /// let mut backoff = ExponentialBackoff::new(Duration::from_millis(200), Duration::from_secs(30), 2.0);
+    ///
+    /// assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+    /// assert_eq!(backoff.backoff_period(1), Duration::from_millis(200));
+    /// assert_eq!(backoff.backoff_period(2), Duration::from_millis(400));
+    /// assert_eq!(backoff.backoff_period(10), Duration::from_secs(30));
+    /// ```
+    pub fn new(initial: Duration, max: Duration, base: f32) -> Self {
+        Self::builder()
+            .initial_interval(initial)
+            .multiplier(base)
+            .max_interval(max)
+            .build()
+    }
+
+    /// Creates an [ExponentialBackoffBuilder](crate::backoff::ExponentialBackoffBuilder)
+    /// for configuring a backoff the same way as
+    /// [new](crate::backoff::ExponentialBackoff::new), one setting at a time.
+    ///
+    /// # Example
+    /// ```
+    /// # use tryagain::*;
+    /// # use std::time::Duration;
+    /// let mut backoff = ExponentialBackoff::builder()
+    ///     .initial_interval(Duration::from_millis(250))
+    ///     .multiplier(2.0)
+    ///     .max_interval(Duration::from_secs(30))
+    ///     .build();
+    ///
+    /// assert_eq!(backoff.backoff_period(1), Duration::from_millis(250));
+    /// assert_eq!(backoff.backoff_period(2), Duration::from_millis(500));
+    /// assert_eq!(backoff.backoff_period(10), Duration::from_secs(30));
+    /// ```
+    pub fn builder() -> ExponentialBackoffBuilder {
+        ExponentialBackoffBuilder::default()
+    }
+
     /// Creates an [ExponentialBackoff](crate::backoff::ExponentialBackoff) with a base for the exponential
     /// function used to calculate backoff duration.
     ///
     /// Equation: `delay = 100(base^iterations - 1)`
+    ///
+    /// # Panics
+    /// Panics if `base` isn't a finite number greater than `1.0`, as the delay
+    /// would never grow and the retry would spin without waiting. Use
+    /// [try_with_base](crate::backoff::ExponentialBackoff::try_with_base) to
+    /// handle an invalid base instead.
     pub fn with_base(base: f32) -> Self {
-        Self {
+        match Self::try_with_base(base) {
+            Ok(backoff) => backoff,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// The same as [with_base](crate::backoff::ExponentialBackoff::with_base)
+    /// but returns an error instead of panicking if `base` isn't a finite
+    /// number greater than `1.0`.
+    ///
+    /// # Example
+    /// ```
+    /// # use tryagain::*;
+    /// assert!(ExponentialBackoff::try_with_base(2.0).is_ok());
+    /// assert_eq!(ExponentialBackoff::try_with_base(0.5).unwrap_err().base(), 0.5);
+    /// ```
+    pub fn try_with_base(base: f32) -> Result<Self, InvalidBase> {
+        validate_base(base)?;
+
+        Ok(Self {
             base,
+            initial: None,
+            initial_iteration: 0,
+            max: None,
+            max_elapsed: None,
+            first_attempt_delay: false,
+            #[cfg(feature = "jitter")]
+            jitter: None,
+            #[cfg(feature = "jitter")]
+            jitter_seed: fastrand::u64(..),
+        })
+    }
+
+    /// Creates an [ExponentialBackoff](crate::backoff::ExponentialBackoff) with
+    /// a base picked at random from `range`, so that instances with the same
+    /// configuration don't retry in lockstep. The base is fixed for the lifetime
+    /// of the instance.
+    ///
+    /// # Panics
+    /// Panics if `range` is empty or doesn't start above `1.0`.
+    #[cfg(feature = "jitter")]
+    pub fn with_random_base(range: core::ops::Range<f32>) -> Self {
+        Self::with_seeded_random_base(range, fastrand::u64(..))
+    }
+
+    /// The same as
+    /// [with_random_base](crate::backoff::ExponentialBackoff::with_random_base)
+    /// but the base is picked using `seed`, making it reproducible.
+    ///
+    /// # Panics
+    /// Panics if `range` is empty or doesn't start above `1.0`.
+    #[cfg(feature = "jitter")]
+    pub fn with_seeded_random_base(range: core::ops::Range<f32>, seed: u64) -> Self {
+        assert!(!range.is_empty(), "ExponentialBackoff base range must not be empty");
+        assert!(range.start > 1.0, "ExponentialBackoff base range must start above 1");
+
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let base = range.start + (range.end - range.start) * rng.f32();
+        Self::with_base(base).with_jitter_seed(rng.u64(..))
+    }
+
+    /// Sets the initial interval that is scaled by the exponential function.
+    ///
+    /// Equation: `delay = initial * base^iterations`
+    pub fn with_initial(mut self, initial: Duration) -> Self {
+        self.initial = Some(initial);
+        self
+    }
+
+    /// Sets the maximum interval that will ever be returned.
+    ///
+    /// Equation: `delay = min(max, delay)`
+    pub fn with_max(mut self, max: Duration) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Sets whether iteration `0` is delayed when no initial interval is
+    /// configured.
+    ///
+    /// Equation: `delay = 100(base^(iterations + 1) - 1)`
+    pub fn first_attempt_delay(mut self, first_attempt_delay: bool) -> Self {
+        self.first_attempt_delay = first_attempt_delay;
+        self
+    }
+
+    /// Sets the maximum time since the first attempt after which the backoff
+    /// gives up and the last error is returned.
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// The delay in milliseconds given by the formula for `iterations`, before
+    /// any jitter or maximum interval is applied. The default formula is
+    /// truncated to whole milliseconds.
+    ///
+    /// # Example
+    /// ```
+    /// # use tryagain::*;
+    /// # use std::time::Duration;
+    /// let backoff = ExponentialBackoff::with_base(2.0);
+    ///
+    /// assert_eq!(backoff.delay_millis(0), 0.0);
+    /// assert_eq!(backoff.delay_millis(3), 700.0);
+    ///
+    /// let backoff = ExponentialBackoff::with_base(2.0).with_initial(Duration::from_millis(250));
+    /// assert_eq!(backoff.delay_millis(3), 2000.0);
+    /// ```
+    pub fn delay_millis(&self, iterations: u32) -> f64 {
+        match self.initial {
+            Some(_) if iterations < self.initial_iteration => 0.0,
+            Some(initial) => {
+                let exp = iterations - self.initial_iteration;
+                initial.as_nanos() as f64 / 1e6 * powi(self.base as f64, exp)
+            }
+            None => {
+                let shift = self.first_attempt_delay as u32;
+                let factor = powi(self.base as f64, iterations.saturating_add(shift));
+                let millis = (factor - 1.0) * 100.0;
+
+                // The default formula is measured in whole milliseconds.
+                if millis < u64::MAX as f64 {
+                    millis as u64 as f64
+                } else {
+                    millis
+                }
+            }
         }
     }
+
+    /// Sets the fraction by which every delay is randomly scaled up or down,
+    /// `0.1` varies each delay within 10% of its value. The jitter is applied
+    /// before the maximum interval.
+    ///
+    /// Equation: `delay = delay * (1 + random(-fraction, fraction))`
+    ///
+    /// # Panics
+    /// Panics if `fraction` is not between `0.0` and `1.0`, inclusive.
+    #[cfg(feature = "jitter")]
+    pub fn with_jitter(mut self, fraction: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "ExponentialBackoff jitter fraction must be between 0 and 1"
+        );
+
+        self.jitter = Some(fraction);
+        self
+    }
+
+    /// Seeds the random number generator used for jitter, making the sequence
+    /// of delays reproducible.
+    #[cfg(feature = "jitter")]
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = seed;
+        self
+    }
+
+    #[cfg(feature = "jitter")]
+    fn jitter(&mut self, duration: Duration) -> Duration {
+        let fraction = match self.jitter {
+            Some(fraction) => fraction as f64,
+            None => return duration,
+        };
+
+        let mut rng = fastrand::Rng::with_seed(self.jitter_seed);
+        let factor = 1.0 + fraction * (rng.f64() * 2.0 - 1.0);
+        self.jitter_seed = rng.get_seed();
+
+        saturating_mul(duration, factor)
+    }
 }
 
 impl Backoff for ExponentialBackoff {
     fn backoff_period(&mut self, iterations: u32) -> Duration {
-        let y = self.base.powi(iterations as i32) - 1.0;
-        Duration::from_millis((y * 100.0) as u64)
+        let duration = duration_from_millis_f64(self.delay_millis(iterations));
+        #[cfg(feature = "jitter")]
+        let duration = self.jitter(duration);
+
+        match self.max {
+            Some(max) => duration.min(max),
+            None => duration,
+        }
     }
-}
 
-impl Default for ExponentialBackoff {
-    fn default() -> Self {
-        Self {
-            base: 1.25,
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        match self.max_elapsed {
+            Some(max_elapsed) if ctx.elapsed >= max_elapsed => None,
+            _ => Some(self.compute(ctx)),
         }
     }
 }
 
-/// A [Backoff](crate::backoff::Backoff) implementation that doesn't have
-/// any delay and retries immediately.
-pub struct ImmediateBackoff;
+/// Builds an [ExponentialBackoff](crate::backoff::ExponentialBackoff) that
+/// waits `initial_interval` after the first failure and multiplies the delay
+/// by `multiplier` after every following failure, never exceeding
+/// `max_interval`.
+///
+/// By default the initial interval is 100 milliseconds, the multiplier is `2`
+/// and there is no maximum interval.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialBackoffBuilder {
+    initial_interval: Duration,
+    multiplier: f32,
+    max_interval: Option<Duration>,
+}
 
-impl Backoff for ImmediateBackoff {
-    fn backoff_period(&mut self, _iterations: u32) -> Duration {
-        Duration::from_secs(0)
+impl ExponentialBackoffBuilder {
+    /// Sets the delay after the first failure.
+    pub fn initial_interval(mut self, initial_interval: Duration) -> Self {
+        self.initial_interval = initial_interval;
+        self
     }
-}
 
-/// A [Backoff](crate::backoff::Backoff) implementation with a minimum duration
-/// that must be reached before a retry attempt can be made.
-pub struct MinimumBackoff<T: Backoff> {
-    inner: T,
-    min_duration: Duration,
+    /// Sets the factor the delay is multiplied by after every failure.
+    ///
+    /// [build](crate::backoff::ExponentialBackoffBuilder::build) panics if it
+    /// isn't a finite number greater than `1.0`.
+    pub fn multiplier(mut self, multiplier: f32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets the maximum interval that will ever be returned.
+    pub fn max_interval(mut self, max_interval: Duration) -> Self {
+        self.max_interval = Some(max_interval);
+        self
+    }
+
+    /// Creates the configured [ExponentialBackoff](crate::backoff::ExponentialBackoff).
+    pub fn build(self) -> ExponentialBackoff {
+        let mut backoff = ExponentialBackoff::with_base(self.multiplier).with_initial(self.initial_interval);
+        backoff.initial_iteration = 1;
+        backoff.max = self.max_interval;
+        backoff
+    }
 }
 
-impl<T: Backoff> MinimumBackoff<T> {
-    pub fn new(inner: T, min_duration: Duration) -> Self {
+impl Default for ExponentialBackoffBuilder {
+    fn default() -> Self {
         Self {
-            inner,
-            min_duration,
+            initial_interval: Duration::from_millis(100),
+            multiplier: 2.0,
+            max_interval: None,
         }
     }
 }
 
-impl<T: Backoff> Backoff for MinimumBackoff<T> {
-    fn backoff_period(&mut self, iterations: u32) -> Duration {
-        self.min_duration.max(self.inner.backoff_period(iterations))
-    }
-}
+/// Raises `base` to the power of `exp` by squaring, the same way `f64::powi`
+/// does, without depending on `std`. The exponent is unsigned so it can't wrap
+/// around to a negative power, overflowing results are infinite.
+fn powi(mut base: f64, mut exp: u32) -> f64 {
+    let mut result = 1.0;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    loop {
+        if exp & 1 == 1 {
+            result *= base;
+        }
 
-    #[test]
-    fn text_default_exponential() {
-        let mut backoff = ExponentialBackoff::default();
+        exp /= 2;
+        if exp == 0 {
+            return result;
+        }
 
-        assert_eq!(backoff.backoff_period(0), Duration::from_millis(0));
-        assert_eq!(backoff.backoff_period(1), Duration::from_millis(25));
-        assert_eq!(backoff.backoff_period(2), Duration::from_millis(56));
-        assert_eq!(backoff.backoff_period(3), Duration::from_millis(95));
+        base *= base;
     }
+}
 
-    #[test]
-    fn test_exponential_with_base() {
-        let mut backoff = ExponentialBackoff::with_base(10.0);
+/// Converts a number of milliseconds to a duration, rounded to the nearest
+/// nanosecond. Overflowing values saturate at
+/// [Duration::MAX](core::time::Duration::MAX), negative values and `NaN` are
+/// floored at zero.
+pub(crate) fn duration_from_millis_f64(ms: f64) -> Duration {
+    if ms.is_nan() || ms <= 0.0 {
+        return Duration::ZERO;
+    }
 
-        assert_eq!(backoff.backoff_period(0), Duration::from_millis(00000));
-        assert_eq!(backoff.backoff_period(1), Duration::from_millis(00900));
-        assert_eq!(backoff.backoff_period(2), Duration::from_millis(09900));
-        assert_eq!(backoff.backoff_period(3), Duration::from_millis(99900));
+    let nanos = ms * 1e6 + 0.5;
+    if nanos < u64::MAX as f64 {
+        Duration::from_nanos(nanos as u64)
+    } else {
+        Duration::try_from_secs_f64(ms / 1e3).unwrap_or(Duration::MAX)
+    }
+}
+
+/// The 64 bit FNV-1a hash, whose output is fixed unlike that of
+/// `DefaultHasher`.
+struct Fnv1aHasher {
+    hash: u64,
+}
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self {
+            hash: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+}
+
+impl core::hash::Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Converts a number of nanoseconds to a duration, saturating at
+/// [Duration::MAX](core::time::Duration::MAX).
+fn duration_from_nanos(nanos: u128) -> Duration {
+    let secs = nanos / 1_000_000_000;
+    if secs > u64::MAX as u128 {
+        return Duration::MAX;
+    }
+
+    Duration::new(secs as u64, (nanos % 1_000_000_000) as u32)
+}
+
+/// Multiplies a duration by `factor`, saturating at
+/// [Duration::MAX](core::time::Duration::MAX) and flooring negative results at
+/// zero.
+fn saturating_mul(duration: Duration, factor: f64) -> Duration {
+    duration_from_millis_f64(duration.as_nanos() as f64 / 1e6 * factor)
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self::with_base(1.25)
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that doesn't have
+/// any delay and retries immediately.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImmediateBackoff;
+
+impl Backoff for ImmediateBackoff {
+    fn backoff_period(&mut self, _iterations: u32) -> Duration {
+        Duration::from_secs(0)
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that always waits the
+/// same duration.
+///
+/// Its constructors are `const`, so a schedule can be declared in a `const` or
+/// `static`.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// const BACKOFF: ConstantBackoff = ConstantBackoff::from_millis(250);
+///
+/// let mut backoff = BACKOFF;
+/// assert_eq!(backoff.backoff_period(3), Duration::from_millis(250));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConstantBackoff {
+    period: Duration,
+}
+
+impl ConstantBackoff {
+    /// Creates a [ConstantBackoff](crate::backoff::ConstantBackoff) that waits
+    /// `period` between every attempt.
+    pub const fn new(period: Duration) -> Self {
+        Self {
+            period,
+        }
+    }
+
+    /// Creates a [ConstantBackoff](crate::backoff::ConstantBackoff) that waits
+    /// `millis` milliseconds between every attempt.
+    pub const fn from_millis(millis: u64) -> Self {
+        Self::new(Duration::from_millis(millis))
+    }
+}
+
+impl Backoff for ConstantBackoff {
+    fn backoff_period(&mut self, _iterations: u32) -> Duration {
+        self.period
+    }
+}
+
+/// A [Duration](core::time::Duration) is a backoff that always waits itself,
+/// the same as a [ConstantBackoff](crate::backoff::ConstantBackoff).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// fn connect() -> Result<(), ()> {
+/// #   return Ok(()); // So our doctests pass.
+///     Err(())
+/// }
+///
+/// tryagain::retry(Duration::from_secs(2), connect);
+/// ```
+impl Backoff for Duration {
+    fn backoff_period(&mut self, _iterations: u32) -> Duration {
+        *self
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that increases the delay
+/// by a fixed increment every iteration.
+///
+/// Equation: `delay = start + increment * iterations`
+///
+/// Delays that are too large to be represented saturate at
+/// [Duration::MAX](core::time::Duration::MAX), and are capped by the interval
+/// configured with [with_max](crate::backoff::LinearBackoff::with_max), if any.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// let mut backoff = LinearBackoff::new(Duration::from_millis(500));
+///
+/// assert_eq!(backoff.backoff_period(1), Duration::from_millis(500));
+/// assert_eq!(backoff.backoff_period(2), Duration::from_secs(1));
+/// assert_eq!(backoff.backoff_period(3), Duration::from_millis(1500));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearBackoff {
+    start: Duration,
+    increment: Duration,
+    max: Option<Duration>,
+}
+
+impl LinearBackoff {
+    /// Creates a [LinearBackoff](crate::backoff::LinearBackoff) starting at
+    /// zero.
+    pub fn new(increment: Duration) -> Self {
+        Self::with_start(Duration::ZERO, increment)
+    }
+
+    /// Creates a [LinearBackoff](crate::backoff::LinearBackoff) that waits
+    /// `start` at iteration `0`.
+    pub fn with_start(start: Duration, increment: Duration) -> Self {
+        Self {
+            start,
+            increment,
+            max: None,
+        }
+    }
+
+    /// Sets the maximum interval, the delay plateaus once it reaches it.
+    pub fn with_max(mut self, max: Duration) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl Backoff for LinearBackoff {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self
+            .increment
+            .checked_mul(iterations)
+            .and_then(|increment| self.start.checked_add(increment))
+            .unwrap_or(Duration::MAX);
+
+        match self.max {
+            Some(max) => duration.min(max),
+            None => duration,
+        }
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation where the delay grows
+/// polynomially with the number of iterations, faster than a
+/// [LinearBackoff](crate::backoff::LinearBackoff) but slower than an
+/// [ExponentialBackoff](crate::backoff::ExponentialBackoff).
+///
+/// Equation: `delay = unit * iterations^exponent`
+///
+/// Delays that are too large to be represented saturate at
+/// [Duration::MAX](core::time::Duration::MAX), and are capped by the interval
+/// configured with [with_max](crate::backoff::PolynomialBackoff::with_max), if
+/// any.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// # async {
+/// async fn poll_job() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// // Waits 10ms, 40ms, 90ms, 160ms...
+/// let backoff = PolynomialBackoff::new(Duration::from_millis(10), 2);
+/// let result = tryagain::future::retry_if(backoff, poll_job, |_, iterations| iterations < 5).await;
+/// # };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PolynomialBackoff {
+    unit: Duration,
+    exponent: u32,
+    max: Option<Duration>,
+}
+
+impl PolynomialBackoff {
+    /// Creates a [PolynomialBackoff](crate::backoff::PolynomialBackoff) waiting `unit * iterations^exponent`.
+    pub fn new(unit: Duration, exponent: u32) -> Self {
+        Self {
+            unit,
+            exponent,
+            max: None,
+        }
+    }
+
+    /// Sets the maximum interval, the delay plateaus once it reaches it.
+    pub fn with_max(mut self, max: Duration) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl Backoff for PolynomialBackoff {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = if self.unit.is_zero() {
+            Duration::ZERO
+        } else {
+            iterations
+                .checked_pow(self.exponent)
+                .and_then(|factor| self.unit.checked_mul(factor))
+                .unwrap_or(Duration::MAX)
+        };
+
+        match self.max {
+            Some(max) => duration.min(max),
+            None => duration,
+        }
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that grows the delay
+/// exponentially using only integer arithmetic, for schedules that must be
+/// exact or targets that avoid floating point.
+///
+/// Equation: `delay = min(max, initial * (numerator / denominator)^iterations)`
+///
+/// Integer multipliers are exact. Rational multipliers created with
+/// [with_ratio](crate::backoff::ExactExponentialBackoff::with_ratio) round
+/// down to the nanosecond at every iteration. Delays that are too large to be
+/// represented saturate at [Duration::MAX](core::time::Duration::MAX), or at
+/// the interval configured with
+/// [with_max](crate::backoff::ExactExponentialBackoff::with_max), if any.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// let mut backoff = ExactExponentialBackoff::with_ratio(Duration::from_millis(100), 3, 2);
+///
+/// assert_eq!(backoff.backoff_period(0), Duration::from_millis(100));
+/// assert_eq!(backoff.backoff_period(1), Duration::from_millis(150));
+/// assert_eq!(backoff.backoff_period(2), Duration::from_millis(225));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExactExponentialBackoff {
+    initial: Duration,
+    numerator: u32,
+    denominator: u32,
+    max: Option<Duration>,
+}
+
+impl ExactExponentialBackoff {
+    /// Creates an
+    /// [ExactExponentialBackoff](crate::backoff::ExactExponentialBackoff) that
+    /// waits `initial` at iteration `0` and multiplies the delay by
+    /// `multiplier` every iteration.
+    ///
+    /// # Panics
+    /// Panics if `multiplier` is `0`.
+    pub fn new(initial: Duration, multiplier: u32) -> Self {
+        Self::with_ratio(initial, multiplier, 1)
+    }
+
+    /// Creates an
+    /// [ExactExponentialBackoff](crate::backoff::ExactExponentialBackoff) that
+    /// multiplies the delay by `numerator / denominator` every iteration.
+    ///
+    /// # Panics
+    /// Panics if `denominator` is `0` or greater than `numerator`, as the
+    /// delay must not shrink.
+    pub fn with_ratio(initial: Duration, numerator: u32, denominator: u32) -> Self {
+        assert!(denominator > 0, "ExactExponentialBackoff denominator must not be zero");
+        assert!(
+            numerator >= denominator,
+            "ExactExponentialBackoff multiplier must be at least one"
+        );
+
+        Self {
+            initial,
+            numerator,
+            denominator,
+            max: None,
+        }
+    }
+
+    /// Sets the maximum interval, the delay plateaus once it reaches it.
+    pub fn with_max(mut self, max: Duration) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl Backoff for ExactExponentialBackoff {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let max = self.max.unwrap_or(Duration::MAX).as_nanos();
+        let mut nanos = self.initial.as_nanos().min(max);
+
+        for _ in 0..iterations {
+            let next = nanos
+                .checked_mul(self.numerator as u128)
+                .map_or(max, |nanos| nanos / self.denominator as u128)
+                .min(max);
+
+            // The delay can only grow, once it stops growing it never will.
+            if next == nanos {
+                break;
+            }
+            nanos = next;
+        }
+
+        duration_from_nanos(nanos)
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that waits a uniformly
+/// random duration between a minimum and maximum, regardless of the number of
+/// iterations.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// fn crawl() -> Result<(), ()> {
+/// #   return Ok(()); // So our doctests pass.
+///     Err(())
+/// }
+///
+/// // Waits somewhere between 2 and 5 seconds before every retry.
+/// let backoff = RandomBackoff::new(Duration::from_secs(2), Duration::from_secs(5));
+/// let result = tryagain::retry_if(backoff, crawl, |_, iterations| iterations < 3);
+/// ```
+#[cfg(feature = "jitter")]
+#[derive(Debug, Clone)]
+pub struct RandomBackoff<R: Rng = fastrand::Rng> {
+    min: Duration,
+    max: Duration,
+    rng: R,
+}
+
+#[cfg(feature = "jitter")]
+impl RandomBackoff {
+    /// Creates a [RandomBackoff](crate::backoff::RandomBackoff) that waits
+    /// between `min` and `max`, inclusive.
+    ///
+    /// # Panics
+    /// Panics if `min` is greater than `max`.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self::with_rng(min, max, fastrand::Rng::new())
+    }
+
+    /// Seeds the random number generator, making the sequence of delays
+    /// reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng.seed(seed);
+        self
+    }
+}
+
+#[cfg(feature = "jitter")]
+impl<R: Rng> RandomBackoff<R> {
+    /// Creates a [RandomBackoff](crate::backoff::RandomBackoff) that draws its
+    /// delays from the provided [Rng](crate::rng::Rng), so that a seeded
+    /// generator makes them reproducible.
+    ///
+    /// # Panics
+    /// Panics if `min` is greater than `max`.
+    pub fn with_rng(min: Duration, max: Duration, rng: R) -> Self {
+        assert!(min <= max, "RandomBackoff minimum must not exceed its maximum");
+
+        Self {
+            min,
+            max,
+            rng,
+        }
+    }
+}
+
+#[cfg(feature = "jitter")]
+impl<R: Rng> Backoff for RandomBackoff<R> {
+    fn backoff_period(&mut self, _iterations: u32) -> Duration {
+        uniform(&mut self.rng, self.min, self.max)
+    }
+}
+
+/// Picks a duration between `min` and `max` using `rng`.
+#[cfg(feature = "jitter")]
+fn uniform<R: Rng>(rng: &mut R, min: Duration, max: Duration) -> Duration {
+    min + (max - min).mul_f64(rng.next_f64())
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation using the decorrelated
+/// jitter algorithm, where every delay is random but grows with the previous
+/// one.
+///
+/// Equation: `delay = min(cap, random(base, previous * 3))`
+///
+/// As it remembers the previous delay, it is
+/// [reset](crate::backoff::Backoff::reset) at the start of every retry.
+#[cfg(feature = "jitter")]
+#[derive(Debug, Clone)]
+pub struct DecorrelatedJitterBackoff<R: Rng = fastrand::Rng> {
+    base: Duration,
+    cap: Duration,
+    previous: Duration,
+    rng: R,
+}
+
+#[cfg(feature = "jitter")]
+impl DecorrelatedJitterBackoff {
+    /// Creates a
+    /// [DecorrelatedJitterBackoff](crate::backoff::DecorrelatedJitterBackoff)
+    /// whose delays are always between `base` and `cap`, inclusive.
+    ///
+    /// # Panics
+    /// Panics if `base` is greater than `cap`.
+    pub fn new(base: Duration, cap: Duration) -> Self {
+        Self::with_rng(base, cap, fastrand::Rng::new())
+    }
+
+    /// Seeds the random number generator, making the sequence of delays
+    /// reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng.seed(seed);
+        self
+    }
+}
+
+#[cfg(feature = "jitter")]
+impl<R: Rng> DecorrelatedJitterBackoff<R> {
+    /// Creates a
+    /// [DecorrelatedJitterBackoff](crate::backoff::DecorrelatedJitterBackoff)
+    /// that draws its delays from the provided [Rng](crate::rng::Rng), so
+    /// that a seeded generator makes them reproducible.
+    ///
+    /// # Panics
+    /// Panics if `base` is greater than `cap`.
+    pub fn with_rng(base: Duration, cap: Duration, rng: R) -> Self {
+        assert!(
+            base <= cap,
+            "DecorrelatedJitterBackoff base must not exceed its cap"
+        );
+
+        Self {
+            base,
+            cap,
+            previous: base,
+            rng,
+        }
+    }
+}
+
+#[cfg(feature = "jitter")]
+impl<R: Rng> Backoff for DecorrelatedJitterBackoff<R> {
+    fn backoff_period(&mut self, _iterations: u32) -> Duration {
+        let upper = self.previous.saturating_mul(3).min(self.cap);
+
+        self.previous = uniform(&mut self.rng, self.base, upper);
+        self.previous
+    }
+
+    /// Forgets the previous delay so the next one starts again from `base`.
+    fn reset(&mut self) {
+        self.previous = self.base;
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that plays back a fixed
+/// list of delays, one per iteration.
+///
+/// Iterations past the end of the list either repeat the last delay, which is
+/// the default, or make the backoff give up when
+/// [repeat_last](crate::backoff::SliceBackoff::repeat_last) is disabled.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// let mut backoff = SliceBackoff::new(vec![Duration::from_millis(10), Duration::from_millis(50)]);
+///
+/// assert_eq!(backoff.backoff_period(0), Duration::from_millis(10));
+/// assert_eq!(backoff.backoff_period(1), Duration::from_millis(50));
+/// assert_eq!(backoff.backoff_period(5), Duration::from_millis(50));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SliceBackoff {
+    durations: Vec<Duration>,
+    repeat_last: bool,
+}
+
+impl SliceBackoff {
+    /// Creates a [SliceBackoff](crate::backoff::SliceBackoff) that repeats the last of `durations`.
+    pub fn new(durations: Vec<Duration>) -> Self {
+        Self {
+            durations,
+            repeat_last: true,
+        }
+    }
+
+    /// Sets whether iterations past the end of the list repeat the last delay.
+    /// When disabled the backoff gives up instead.
+    pub fn repeat_last(mut self, repeat_last: bool) -> Self {
+        self.repeat_last = repeat_last;
+        self
+    }
+}
+
+impl Backoff for SliceBackoff {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.durations
+            .get(iterations as usize)
+            .or_else(|| self.durations.last())
+            .copied()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        if !self.repeat_last && ctx.iterations as usize >= self.durations.len() {
+            return None;
+        }
+
+        Some(self.compute(ctx))
+    }
+}
+
+/// What an [IteratorBackoff](crate::backoff::IteratorBackoff) does once its
+/// iterator runs out of delays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnExhausted {
+    /// Keep repeating the last delay, or no delay if there wasn't any.
+    RepeatLast,
+    /// Keep waiting the given delay.
+    Fixed(Duration),
+    /// Give up retrying.
+    GiveUp,
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that takes one delay
+/// from an iterator every time it is asked for one, such as a schedule loaded
+/// from a configuration file.
+///
+/// What happens once the iterator is exhausted is configured with
+/// [on_exhausted](crate::backoff::IteratorBackoff::on_exhausted), by default
+/// the last delay is repeated.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// let schedule = [1, 5, 30, 120].map(Duration::from_secs);
+/// let mut backoff = IteratorBackoff::new(schedule).on_exhausted(OnExhausted::GiveUp);
+///
+/// assert_eq!(backoff.backoff_period(0), Duration::from_secs(1));
+/// assert_eq!(backoff.backoff_period(1), Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IteratorBackoff<I> {
+    iter: I,
+    last: Option<Duration>,
+    on_exhausted: OnExhausted,
+}
+
+impl<I: Iterator<Item = Duration>> IteratorBackoff<I> {
+    /// Creates an [IteratorBackoff](crate::backoff::IteratorBackoff) that repeats the last delay of `iter`.
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            iter: iter.into_iter(),
+            last: None,
+            on_exhausted: OnExhausted::RepeatLast,
+        }
+    }
+
+    /// Sets what happens once the iterator is exhausted.
+    pub fn on_exhausted(mut self, on_exhausted: OnExhausted) -> Self {
+        self.on_exhausted = on_exhausted;
+        self
+    }
+
+    fn next(&mut self) -> Option<Duration> {
+        if let Some(duration) = self.iter.next() {
+            self.last = Some(duration);
+            return Some(duration);
+        }
+
+        match self.on_exhausted {
+            OnExhausted::RepeatLast => Some(self.last.unwrap_or(Duration::ZERO)),
+            OnExhausted::Fixed(duration) => Some(duration),
+            OnExhausted::GiveUp => None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Duration>> Backoff for IteratorBackoff<I> {
+    /// Returns the last delay, or no delay, once the iterator is exhausted
+    /// even if the backoff is configured to give up, as only
+    /// [next_backoff](crate::backoff::Backoff::next_backoff) is able to.
+    fn backoff_period(&mut self, _iterations: u32) -> Duration {
+        self.next().unwrap_or_else(|| self.last.unwrap_or(Duration::ZERO))
+    }
+
+    fn next_backoff(&mut self, _ctx: &BackoffContext) -> Option<Duration> {
+        self.next()
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that calls a closure or
+/// function pointer with the number of iterations to get the delay.
+///
+/// This is a newtype rather than a blanket implementation for closures so that
+/// it doesn't conflict with implementations for references and boxes.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// fn returns_err() -> Result<(), ()> {
+/// #   return Ok(()); // So our doctests pass.
+///     Err(())
+/// }
+///
+/// let backoff = FnBackoff(|iterations| Duration::from_millis(50 * iterations as u64));
+/// let value = tryagain::retry(backoff, returns_err);
+///
+/// // The closure can keep its own state.
+/// let mut delay = Duration::from_millis(10);
+/// let backoff = FnBackoff(move |_| {
+///     delay += Duration::from_millis(10);
+///     delay
+/// });
+/// let value = tryagain::retry(backoff, returns_err);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FnBackoff<F>(pub F);
+
+impl<F> Backoff for FnBackoff<F>
+where
+    F: FnMut(u32) -> Duration,
+{
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        (self.0)(iterations)
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation with a minimum duration
+/// that must be reached before a retry attempt can be made.
+///
+/// The larger of the two durations is always returned unchanged, so an inner
+/// backoff saturating at [Duration::MAX](core::time::Duration::MAX) stays
+/// saturated.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MinimumBackoff<T: Backoff> {
+    inner: T,
+    min_duration: Duration,
+}
+
+impl<T: Backoff> MinimumBackoff<T> {
+    pub fn new(inner: T, min_duration: Duration) -> Self {
+        Self {
+            inner,
+            min_duration,
+        }
+    }
+}
+
+impl<T: Backoff> MinimumBackoff<T> {
+    /// Computes the delay for `iterations` along with the name of the backoff
+    /// that produced it, either the inner backoff or this one when the minimum
+    /// duration was used.
+    pub fn explain(&mut self, iterations: u32) -> BackoffExplanation {
+        let inner = self.inner.backoff_period(iterations);
+
+        if inner >= self.min_duration {
+            BackoffExplanation::new::<T>(inner)
+        } else {
+            BackoffExplanation::new::<Self>(self.min_duration)
+        }
+    }
+}
+
+impl<T: Backoff> Backoff for MinimumBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.explain(iterations).delay
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        self.min_duration.max(self.inner.compute(ctx))
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(self.min_duration.max(duration))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that caps the delay of
+/// another backoff at a maximum duration.
+///
+/// The first iteration at which the cap was applied is recorded, which can be
+/// used as a signal that a failure is severe.
+///
+/// An inner backoff saturating at [Duration::MAX](core::time::Duration::MAX)
+/// is capped like any other delay.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CappedBackoff<T: Backoff> {
+    inner: T,
+    max_duration: Duration,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    first_capped_iteration: Option<u32>,
+}
+
+impl<T: Backoff> CappedBackoff<T> {
+    /// Creates a [CappedBackoff](crate::backoff::CappedBackoff) that never waits longer than `max_duration`.
+    pub fn new(inner: T, max_duration: Duration) -> Self {
+        Self {
+            inner,
+            max_duration,
+            first_capped_iteration: None,
+        }
+    }
+
+    /// Returns the first iteration at which the inner backoff's delay exceeded
+    /// the maximum duration, if it has happened yet.
+    pub fn first_capped_iteration(&self) -> Option<u32> {
+        self.first_capped_iteration
+    }
+}
+
+impl<T: Backoff> CappedBackoff<T> {
+    fn cap(&mut self, duration: Duration, iterations: u32) -> Duration {
+        if duration > self.max_duration {
+            self.first_capped_iteration.get_or_insert(iterations);
+            return self.max_duration;
+        }
+
+        duration
+    }
+}
+
+impl<T: Backoff> Backoff for CappedBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self.inner.backoff_period(iterations);
+        self.cap(duration, iterations)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        let duration = self.inner.compute(ctx);
+        self.cap(duration, ctx.iterations)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(self.cap(duration, ctx.iterations))
+    }
+
+    fn reset(&mut self) {
+        self.first_capped_iteration = None;
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that clamps the delay of
+/// another backoff between a minimum and a maximum duration, the same as
+/// wrapping it in both a [MinimumBackoff](crate::backoff::MinimumBackoff) and a
+/// [CappedBackoff](crate::backoff::CappedBackoff).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// let mut backoff = ClampBackoff::new(
+///     ExponentialBackoff::with_base(10.0),
+///     Duration::from_millis(100),
+///     Duration::from_secs(5),
+/// );
+///
+/// assert_eq!(backoff.backoff_period(0), Duration::from_millis(100));
+/// assert_eq!(backoff.backoff_period(1), Duration::from_millis(900));
+/// assert_eq!(backoff.backoff_period(2), Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClampBackoff<T: Backoff> {
+    inner: T,
+    min_duration: Duration,
+    max_duration: Duration,
+}
+
+impl<T: Backoff> ClampBackoff<T> {
+    /// # Panics
+    /// Panics if `min_duration` is greater than `max_duration`.
+    pub fn new(inner: T, min_duration: Duration, max_duration: Duration) -> Self {
+        assert!(
+            min_duration <= max_duration,
+            "ClampBackoff minimum must not exceed its maximum"
+        );
+
+        Self {
+            inner,
+            min_duration,
+            max_duration,
+        }
+    }
+}
+
+impl<T: Backoff> Backoff for ClampBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self.inner.backoff_period(iterations);
+        duration.clamp(self.min_duration, self.max_duration)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        let duration = self.inner.compute(ctx);
+        duration.clamp(self.min_duration, self.max_duration)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(duration.clamp(self.min_duration, self.max_duration))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that gives up once a
+/// total time budget has been spent, truncating the last delay so that it ends
+/// when the budget does instead of overshooting it.
+#[derive(Debug)]
+pub struct BudgetBackoff<T: Backoff> {
+    inner: T,
+    budget: Duration,
+}
+
+impl<T: Backoff> BudgetBackoff<T> {
+    pub fn new(inner: T, budget: Duration) -> Self {
+        Self {
+            inner,
+            budget,
+        }
+    }
+}
+
+impl<T: Backoff> Backoff for BudgetBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.inner.backoff_period(iterations)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        self.inner.compute(ctx)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let remaining = self
+            .budget
+            .checked_sub(ctx.elapsed)
+            .filter(|remaining| !remaining.is_zero())?;
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(duration.min(remaining))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that waits for the
+/// duration hinted at by the error, such as an HTTP `Retry-After` header, and
+/// falls back to another backoff when there is no hint.
+///
+/// The error is only available to
+/// [retry_if_hinted](crate::sync::retry_if_hinted), when used with the other
+/// retry functions the inner backoff is always used. Errors implementing
+/// [RetryDelayHint](crate::RetryDelayHint) can instead be retried with
+/// [retry_with_hints](crate::sync::retry_with_hints).
+pub struct HintedBackoff<T: Backoff, H> {
+    inner: T,
+    hint: H,
+}
+
+impl<T: Backoff + fmt::Debug, H> fmt::Debug for HintedBackoff<T, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HintedBackoff")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Backoff, H> HintedBackoff<T, H> {
+    pub fn new(inner: T, hint: H) -> Self {
+        Self {
+            inner,
+            hint,
+        }
+    }
+
+    /// The duration that must be waited until the function is tried again
+    /// after failing with `error`, or `None` if the inner backoff gave up.
+    pub fn next_backoff_for<E>(&mut self, error: &E, ctx: &BackoffContext) -> Option<Duration>
+    where
+        H: Fn(&E) -> Option<Duration>,
+    {
+        let fallback = self.inner.next_backoff(ctx)?;
+        Some((self.hint)(error).unwrap_or(fallback))
+    }
+}
+
+impl<T: Backoff, H> Backoff for HintedBackoff<T, H> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.inner.backoff_period(iterations)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        self.inner.compute(ctx)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        self.inner.next_backoff(ctx)
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that multiplies the
+/// delay of another backoff by a factor, keeping its shape while making it
+/// slower or faster.
+///
+/// Delays that are too large to be represented saturate at
+/// [Duration::MAX](core::time::Duration::MAX).
+#[derive(Debug)]
+pub struct ScaledBackoff<T: Backoff> {
+    inner: T,
+    factor: f64,
+}
+
+impl<T: Backoff> ScaledBackoff<T> {
+    /// Creates a [ScaledBackoff](crate::backoff::ScaledBackoff) that multiplies
+    /// the delays of `inner` by `factor`.
+    ///
+    /// # Panics
+    /// Panics if `factor` isn't a finite, non-negative number.
+    pub fn new(inner: T, factor: f64) -> Self {
+        assert!(
+            factor.is_finite() && factor >= 0.0,
+            "ScaledBackoff factor must be a finite, non-negative number, got {}",
+            factor
+        );
+
+        Self {
+            inner,
+            factor,
+        }
+    }
+}
+
+impl<T: Backoff> Backoff for ScaledBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        saturating_mul(self.inner.backoff_period(iterations), self.factor)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        saturating_mul(self.inner.compute(ctx), self.factor)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(saturating_mul(duration, self.factor))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// How a [JitterBackoff](crate::backoff::JitterBackoff) randomizes the delay
+/// of the backoff it wraps, `random` being a number between `0` and `1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JitterStrategy {
+    /// Equation: `delay = delay * random`
+    Full,
+    /// Equation: `delay = delay / 2 + delay / 2 * random`
+    Equal,
+    /// Scales the delay up or down by up to the fraction.
+    ///
+    /// Equation: `delay = delay * (1 + fraction * (2 * random - 1))`
+    Proportional(f64),
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that randomizes the
+/// delay of another backoff according to a
+/// [JitterStrategy](crate::backoff::JitterStrategy), using the provided
+/// [Rng](crate::rng::Rng). This keeps many clients that failed at the same
+/// time from retrying in lockstep.
+///
+/// With the `jitter` feature `JitterBackoff::full`, `JitterBackoff::equal`
+/// and `JitterBackoff::proportional` create one using a randomly seeded
+/// generator.
+#[derive(Debug)]
+pub struct JitterBackoff<T: Backoff, R: Rng> {
+    inner: T,
+    rng: R,
+    strategy: JitterStrategy,
+}
+
+impl<T: Backoff, R: Rng> JitterBackoff<T, R> {
+    /// Creates a [JitterBackoff](crate::backoff::JitterBackoff) using
+    /// [Proportional](crate::backoff::JitterStrategy::Proportional) jitter.
+    pub fn new(inner: T, rng: R, fraction: f64) -> Self {
+        Self::with_strategy(inner, rng, JitterStrategy::Proportional(fraction))
+    }
+
+    /// Creates a [JitterBackoff](crate::backoff::JitterBackoff) using the
+    /// given [JitterStrategy](crate::backoff::JitterStrategy).
+    pub fn with_strategy(inner: T, rng: R, strategy: JitterStrategy) -> Self {
+        Self {
+            inner,
+            rng,
+            strategy,
+        }
+    }
+
+    fn jitter(&mut self, duration: Duration) -> Duration {
+        let random = self.rng.next_f64();
+        let factor = match self.strategy {
+            JitterStrategy::Full => random,
+            JitterStrategy::Equal => 0.5 + random / 2.0,
+            JitterStrategy::Proportional(fraction) => 1.0 + fraction * (random * 2.0 - 1.0),
+        };
+
+        saturating_mul(duration, factor)
+    }
+}
+
+#[cfg(feature = "jitter")]
+impl<T: Backoff> JitterBackoff<T, fastrand::Rng> {
+    /// Creates a [JitterBackoff](crate::backoff::JitterBackoff) using
+    /// [Full](crate::backoff::JitterStrategy::Full) jitter.
+    ///
+    /// # Example
+    /// ```
+    /// # use tryagain::*;
+    /// let backoff = JitterBackoff::full(ExponentialBackoff::default());
+    /// ```
+    pub fn full(inner: T) -> Self {
+        Self::with_strategy(inner, fastrand::Rng::new(), JitterStrategy::Full)
+    }
+
+    /// Creates a [JitterBackoff](crate::backoff::JitterBackoff) using
+    /// [Equal](crate::backoff::JitterStrategy::Equal) jitter.
+    pub fn equal(inner: T) -> Self {
+        Self::with_strategy(inner, fastrand::Rng::new(), JitterStrategy::Equal)
+    }
+
+    /// Creates a [JitterBackoff](crate::backoff::JitterBackoff) using
+    /// [Proportional](crate::backoff::JitterStrategy::Proportional) jitter.
+    pub fn proportional(inner: T, fraction: f64) -> Self {
+        Self::new(inner, fastrand::Rng::new(), fraction)
+    }
+}
+
+impl<T: Backoff, R: Rng> Backoff for JitterBackoff<T, R> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self.inner.backoff_period(iterations);
+        self.jitter(duration)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        let duration = self.inner.compute(ctx);
+        self.jitter(duration)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(self.jitter(duration))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that uses the longest
+/// delay of two other backoffs.
+#[derive(Debug)]
+pub struct MaxBackoff<A: Backoff, B: Backoff> {
+    first: A,
+    second: B,
+}
+
+impl<A: Backoff, B: Backoff> MaxBackoff<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+        }
+    }
+
+    /// Computes the delay for `iterations` along with the name of the inner
+    /// backoff that produced it. When both backoffs produce the same delay the
+    /// first one is reported.
+    pub fn explain(&mut self, iterations: u32) -> BackoffExplanation {
+        let first = self.first.backoff_period(iterations);
+        let second = self.second.backoff_period(iterations);
+
+        if first >= second {
+            BackoffExplanation::new::<A>(first)
+        } else {
+            BackoffExplanation::new::<B>(second)
+        }
+    }
+}
+
+impl<A: Backoff, B: Backoff> Backoff for MaxBackoff<A, B> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.explain(iterations).delay
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        self.first.compute(ctx).max(self.second.compute(ctx))
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let first = self.first.next_backoff(ctx);
+        let second = self.second.next_backoff(ctx);
+
+        Some(first?.max(second?))
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+}
+
+/// A description of the delay chosen by a composite backoff such as
+/// [MaxBackoff](crate::backoff::MaxBackoff), used to debug backoff
+/// configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackoffExplanation {
+    /// The delay that was chosen.
+    pub delay: Duration,
+    /// The type name of the backoff that contributed the delay.
+    pub source: &'static str,
+}
+
+impl BackoffExplanation {
+    fn new<T>(delay: Duration) -> Self {
+        Self {
+            delay,
+            source: core::any::type_name::<T>(),
+        }
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that uses one backoff
+/// for the first iterations and then switches to another.
+///
+/// The second backoff is called with the number of iterations since the
+/// switch, so it starts from iteration `0`.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// // Retry immediately three times, then back off exponentially.
+/// let backoff = PhasedBackoff::new(ImmediateBackoff, 3, ExponentialBackoff::default());
+/// ```
+#[derive(Debug)]
+pub struct PhasedBackoff<A: Backoff, B: Backoff> {
+    first: A,
+    switch_after: u32,
+    second: B,
+}
+
+impl<A: Backoff, B: Backoff> PhasedBackoff<A, B> {
+    pub fn new(first: A, switch_after: u32, second: B) -> Self {
+        Self {
+            first,
+            switch_after,
+            second,
+        }
+    }
+
+    fn second_context(&self, ctx: &BackoffContext) -> BackoffContext {
+        BackoffContext {
+            iterations: ctx.iterations - self.switch_after,
+            ..*ctx
+        }
+    }
+}
+
+impl<A: Backoff, B: Backoff> Backoff for PhasedBackoff<A, B> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        if iterations < self.switch_after {
+            self.first.backoff_period(iterations)
+        } else {
+            self.second.backoff_period(iterations - self.switch_after)
+        }
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        if ctx.iterations < self.switch_after {
+            self.first.compute(ctx)
+        } else {
+            let ctx = self.second_context(ctx);
+            self.second.compute(&ctx)
+        }
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        if ctx.iterations < self.switch_after {
+            self.first.next_backoff(ctx)
+        } else {
+            let ctx = self.second_context(ctx);
+            self.second.next_backoff(&ctx)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that can be frozen at
+/// its most recently computed delay, stopping it from growing any further.
+///
+/// The backoff is controlled through a [FreezeHandle](crate::backoff::FreezeHandle)
+/// which can be cloned and shared with other threads while the backoff itself
+/// is owned by a retry operation.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// let backoff = FreezableBackoff::new(ExponentialBackoff::default());
+/// let handle = backoff.handle();
+///
+/// // Somewhere else, possibly on another thread.
+/// handle.freeze();
+/// ```
+#[derive(Debug)]
+pub struct FreezableBackoff<T: Backoff> {
+    inner: T,
+    handle: FreezeHandle,
+    last: Option<Duration>,
+}
+
+impl<T: Backoff> FreezableBackoff<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            handle: FreezeHandle::default(),
+            last: None,
+        }
+    }
+
+    /// Returns a [FreezeHandle](crate::backoff::FreezeHandle) that controls
+    /// this backoff.
+    pub fn handle(&self) -> FreezeHandle {
+        self.handle.clone()
+    }
+
+    /// Freezes the backoff at its most recently computed delay.
+    pub fn freeze(&self) {
+        self.handle.freeze();
+    }
+
+    /// Allows the backoff to continue growing with the inner backoff.
+    pub fn unfreeze(&self) {
+        self.handle.unfreeze();
+    }
+}
+
+impl<T: Backoff> FreezableBackoff<T> {
+    fn next(&mut self, compute: impl FnOnce(&mut T) -> Option<Duration>) -> Option<Duration> {
+        if self.handle.is_frozen() && self.last.is_some() {
+            return self.last;
+        }
+
+        let duration = compute(&mut self.inner)?;
+        self.last = Some(duration);
+        Some(duration)
+    }
+}
+
+impl<T: Backoff> Backoff for FreezableBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self.next(|inner| Some(inner.backoff_period(iterations)));
+        duration.unwrap_or_default()
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        let duration = self.next(|inner| Some(inner.compute(ctx)));
+        duration.unwrap_or_default()
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        self.next(|inner| inner.next_backoff(ctx))
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+        self.inner.reset();
+    }
+}
+
+/// A shared control used to freeze and unfreeze a
+/// [FreezableBackoff](crate::backoff::FreezableBackoff).
+#[derive(Debug, Clone, Default)]
+pub struct FreezeHandle {
+    frozen: Arc<AtomicBool>,
+}
+
+impl FreezeHandle {
+    /// Freezes the backoff at its most recently computed delay.
+    pub fn freeze(&self) {
+        self.frozen.store(true, Ordering::SeqCst);
+    }
+
+    /// Allows the backoff to continue growing with the inner backoff.
+    pub fn unfreeze(&self) {
+        self.frozen.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns true if the backoff is currently frozen.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_exponential_random_base() {
+        let bases: Vec<f32> = (0..5)
+            .map(|seed| ExponentialBackoff::with_seeded_random_base(1.5..2.5, seed).base)
+            .collect();
+
+        for (i, base) in bases.iter().enumerate() {
+            assert!((1.5..2.5).contains(base), "{} out of range", base);
+            assert!(!bases[..i].contains(base), "{} drawn twice", base);
+        }
+
+        assert_eq!(
+            ExponentialBackoff::with_seeded_random_base(1.5..2.5, 7).base,
+            ExponentialBackoff::with_seeded_random_base(1.5..2.5, 7).base
+        );
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_exponential_without_jitter() {
+        let mut backoff = ExponentialBackoff::default().with_jitter(0.0).with_jitter_seed(7);
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(0));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(25));
+        assert_eq!(backoff.backoff_period(2), Duration::from_millis(56));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(95));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_exponential_jitter() {
+        let mut backoff = ExponentialBackoff::default().with_jitter(0.1).with_jitter_seed(7);
+
+        for (iterations, millis) in [(1, 25.0), (2, 56.0), (3, 95.0)] {
+            for _ in 0..100 {
+                let delay = backoff.backoff_period(iterations).as_secs_f64() * 1000.0;
+                assert!(delay >= millis * 0.9 && delay <= millis * 1.1, "{} out of range", delay);
+            }
+        }
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_exponential_jitter_is_seeded() {
+        let mut first = ExponentialBackoff::default().with_jitter(0.5).with_jitter_seed(7);
+        let mut second = ExponentialBackoff::default().with_jitter(0.5).with_jitter_seed(7);
+
+        for iterations in 0..10 {
+            assert_eq!(first.backoff_period(iterations), second.backoff_period(iterations));
+        }
+    }
+
+    #[test]
+    fn test_exponential_new() {
+        let initial = Duration::from_millis(200);
+        let max = Duration::from_secs(30);
+        let mut backoff = ExponentialBackoff::new(initial, max, 2.0);
+
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+        assert_eq!(backoff.backoff_period(1), initial);
+        assert_eq!(backoff.backoff_period(2), initial * 2);
+        assert_eq!(backoff.backoff_period(3), initial * 4);
+
+        assert_eq!(backoff.backoff_period(8), Duration::from_millis(25600));
+        for iterations in 9..20 {
+            assert_eq!(backoff.backoff_period(iterations), max);
+        }
+    }
+
+    #[test]
+    fn test_debug() {
+        use alloc::format;
+
+        let immediate = format!("{:?}", ImmediateBackoff);
+        let minimum = format!("{:?}", MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1)));
+        let hinted = format!("{:?}", HintedBackoff::new(ImmediateBackoff, |_: &()| None::<Duration>));
+
+        assert_eq!(immediate, "ImmediateBackoff");
+        assert!(minimum.contains("ImmediateBackoff") && minimum.contains("1s"));
+        assert!(hinted.starts_with("HintedBackoff") && hinted.contains("ImmediateBackoff"));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_decorrelated_jitter_within_range() {
+        let (base, cap) = (Duration::from_millis(100), Duration::from_secs(10));
+        let mut backoff = DecorrelatedJitterBackoff::new(base, cap).with_seed(7);
+
+        let mut previous = base;
+        for iterations in 0..1000 {
+            let duration = backoff.backoff_period(iterations);
+            assert!(duration >= base && duration <= cap);
+            assert!(duration <= previous * 3);
+            previous = duration;
+        }
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_decorrelated_jitter_varies() {
+        let (base, cap) = (Duration::from_millis(100), Duration::from_secs(10));
+        let mut first = DecorrelatedJitterBackoff::new(base, cap).with_seed(7);
+        let mut second = DecorrelatedJitterBackoff::new(base, cap).with_seed(8);
+
+        let first: Vec<_> = (0..10).map(|iterations| first.backoff_period(iterations)).collect();
+        let second: Vec<_> = (0..10).map(|iterations| second.backoff_period(iterations)).collect();
+        assert_ne!(first, second);
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_decorrelated_jitter_with_rng() {
+        let (base, cap) = (Duration::from_millis(100), Duration::from_secs(1));
+        let rng = SequenceRng(&[0.5, 0.5, 0.8, 0.0]);
+        let mut backoff = DecorrelatedJitterBackoff::with_rng(base, cap, rng);
+        let schedule: Vec<_> = (0..4).map(|iterations| backoff.backoff_period(iterations)).collect();
+
+        // Each delay is drawn between base and three times the previous one.
+        assert_eq!(schedule, [200, 350, 820, 100].map(Duration::from_millis));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_seeded_golden_sequence() {
+        let (base, cap) = (Duration::from_millis(100), Duration::from_secs(10));
+
+        let schedule = |seed| {
+            let mut random = RandomBackoff::new(base, cap).with_seed(seed);
+            let mut decorrelated = DecorrelatedJitterBackoff::new(base, cap).with_seed(seed);
+            let mut jitter = JitterBackoff::with_strategy(ConstantBackoff::new(cap), fastrand::Rng::with_seed(seed), JitterStrategy::Full);
+
+            (0..20)
+                .map(|iterations| {
+                    (
+                        random.backoff_period(iterations),
+                        decorrelated.backoff_period(iterations),
+                        jitter.backoff_period(iterations),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(schedule(42), schedule(42));
+        assert_ne!(schedule(42), schedule(43));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_decorrelated_jitter_reset() {
+        let (base, cap) = (Duration::from_millis(100), Duration::from_secs(10));
+        let mut backoff = DecorrelatedJitterBackoff::new(base, cap).with_seed(7);
+
+        for iterations in 0..100 {
+            backoff.backoff_period(iterations);
+        }
+
+        backoff.reset();
+        assert!(backoff.backoff_period(0) <= base * 3);
+    }
+
+    #[test]
+    fn test_slice_backoff() {
+        let durations = [1, 2, 3].map(Duration::from_millis);
+        let ctx = |iterations| BackoffContext {
+            iterations,
+            previous: None,
+            elapsed: Duration::ZERO,
+        };
+
+        let mut backoff = SliceBackoff::new(durations.to_vec());
+        for (iterations, duration) in durations.iter().enumerate() {
+            assert_eq!(backoff.backoff_period(iterations as u32), *duration);
+        }
+        assert_eq!(backoff.next_backoff(&ctx(3)), Some(Duration::from_millis(3)));
+        assert_eq!(backoff.next_backoff(&ctx(100)), Some(Duration::from_millis(3)));
+
+        let mut backoff = SliceBackoff::new(durations.to_vec()).repeat_last(false);
+        assert_eq!(backoff.next_backoff(&ctx(2)), Some(Duration::from_millis(3)));
+        assert_eq!(backoff.next_backoff(&ctx(3)), None);
+
+        let mut backoff = SliceBackoff::new(Vec::new());
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+    }
+
+    struct SequenceRng(&'static [f64]);
+
+    impl Rng for SequenceRng {
+        fn next_f64(&mut self) -> f64 {
+            let (first, rest) = self.0.split_first().unwrap();
+            self.0 = rest;
+            *first
+        }
+    }
+
+    #[test]
+    fn test_jitter_backoff() {
+        let inner = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(100));
+        let mut backoff = JitterBackoff::new(inner, SequenceRng(&[0.0, 0.5, 0.75]), 0.5);
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(50));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(100));
+        assert_eq!(backoff.backoff_period(2), Duration::from_millis(125));
+    }
+
+    #[test]
+    fn test_jitter_strategies() {
+        let inner = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(100));
+        let mut backoff = JitterBackoff::with_strategy(inner, SequenceRng(&[0.0, 0.5, 0.75]), JitterStrategy::Full);
+
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(50));
+        assert_eq!(backoff.backoff_period(2), Duration::from_millis(75));
+
+        let inner = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(100));
+        let mut backoff = JitterBackoff::with_strategy(inner, SequenceRng(&[0.0, 0.5, 0.75]), JitterStrategy::Equal);
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(50));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(75));
+        assert_eq!(backoff.backoff_period(2), Duration::from_micros(87500));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_jitter_strategy_bounds() {
+        let delay = Duration::from_millis(100);
+        let strategies = [
+            (JitterStrategy::Full, Duration::ZERO, delay),
+            (JitterStrategy::Equal, delay / 2, delay),
+            (JitterStrategy::Proportional(0.2), delay * 4 / 5, delay * 6 / 5),
+        ];
+
+        for (strategy, min, max) in strategies {
+            let inner = MinimumBackoff::new(ImmediateBackoff, delay);
+            let mut backoff = JitterBackoff::with_strategy(inner, fastrand::Rng::with_seed(7), strategy);
+
+            for iterations in 0..1000 {
+                let duration = backoff.backoff_period(iterations);
+                assert!(duration >= min && duration <= max, "{:?} out of range for {:?}", duration, strategy);
+            }
+        }
+
+        let mut backoff = JitterBackoff::full(ExponentialBackoff::default());
+        assert!(backoff.backoff_period(1) <= Duration::from_millis(25));
+        let mut backoff = JitterBackoff::equal(ExponentialBackoff::default());
+        assert!(backoff.backoff_period(1) >= Duration::from_micros(12500));
+        let mut backoff = JitterBackoff::proportional(ExponentialBackoff::default(), 0.0);
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(25));
+    }
+
+    #[test]
+    fn test_scaled_backoff() {
+        let mut backoff = ScaledBackoff::new(ExponentialBackoff::default(), 2.0);
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(0));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(50));
+        assert_eq!(backoff.backoff_period(2), Duration::from_millis(112));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(190));
+
+        let mut backoff = ScaledBackoff::new(MinimumBackoff::new(ImmediateBackoff, Duration::MAX), 2.0);
+        assert_eq!(backoff.backoff_period(0), Duration::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "ScaledBackoff factor must be a finite, non-negative number, got -1")]
+    fn test_scaled_negative_factor() {
+        ScaledBackoff::new(ImmediateBackoff, -1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "ScaledBackoff factor must be a finite, non-negative number, got NaN")]
+    fn test_scaled_nan_factor() {
+        ImmediateBackoff.scale(f64::NAN);
+    }
+
+    #[test]
+    fn test_exponential_overflow() {
+        let mut backoff = ExponentialBackoff::default();
+        assert_eq!(backoff.backoff_period(i32::MAX as u32 + 1), Duration::MAX);
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::MAX);
+
+        let mut backoff = ExponentialBackoff::with_base(10.0);
+        assert_eq!(backoff.backoff_period(50), Duration::MAX);
+        assert_eq!(backoff.backoff_period(400), Duration::MAX);
+
+        let mut backoff = ExponentialBackoff::with_base(10.0).with_max(Duration::from_secs(3600));
+        assert_eq!(backoff.backoff_period(50), Duration::from_secs(3600));
+        assert_eq!(backoff.backoff_period(i32::MAX as u32 + 1), Duration::from_secs(3600));
+
+        let mut backoff = ExponentialBackoff::new(Duration::from_secs(1), Duration::from_secs(3600), 10.0);
+        assert_eq!(backoff.backoff_period(50), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_exponential_precision() {
+        let backoff = ExponentialBackoff::default();
+        assert_eq!(backoff.delay_millis(1), 25.0);
+        assert_eq!(backoff.delay_millis(2), 56.0);
+
+        // 10^9 isn't representable as an f32 after being scaled.
+        let backoff = ExponentialBackoff::with_base(10.0);
+        assert_eq!(backoff.delay_millis(9), 99_999_999_900.0);
+        assert_eq!(backoff.delay_millis(12), 99_999_999_999_900.0);
+    }
+
+    #[test]
+    fn test_duration_from_millis_f64() {
+        assert_eq!(duration_from_millis_f64(f64::NAN), Duration::ZERO);
+        assert_eq!(duration_from_millis_f64(-5.0), Duration::ZERO);
+        assert_eq!(duration_from_millis_f64(f64::NEG_INFINITY), Duration::ZERO);
+        assert_eq!(duration_from_millis_f64(0.0), Duration::ZERO);
+        assert_eq!(duration_from_millis_f64(250.0), Duration::from_millis(250));
+        assert_eq!(duration_from_millis_f64(1.5), Duration::from_micros(1500));
+        assert_eq!(duration_from_millis_f64(1e30), Duration::MAX);
+        assert_eq!(duration_from_millis_f64(f64::INFINITY), Duration::MAX);
+    }
+
+    #[test]
+    fn test_saturating_composition() {
+        let mut exponential = ExponentialBackoff::with_base(2.0);
+        assert_eq!(exponential.backoff_period(u32::MAX), Duration::MAX);
+
+        let mut exponential = ExponentialBackoff::with_base(2.0).with_initial(Duration::MAX);
+        assert_eq!(exponential.backoff_period(u32::MAX), Duration::MAX);
+
+        let mut minimum = MinimumBackoff::new(ExponentialBackoff::with_base(2.0), Duration::MAX);
+        assert_eq!(minimum.backoff_period(0), Duration::MAX);
+        assert_eq!(minimum.backoff_period(u32::MAX), Duration::MAX);
+
+        let mut minimum = MinimumBackoff::new(ExponentialBackoff::with_base(2.0), Duration::from_secs(1));
+        assert_eq!(minimum.backoff_period(1000), Duration::MAX);
+
+        let mut capped = CappedBackoff::new(
+            MinimumBackoff::new(ExponentialBackoff::with_base(2.0), Duration::MAX),
+            Duration::from_secs(60),
+        );
+        assert_eq!(capped.backoff_period(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_budget_backoff() {
+        let mut backoff = BudgetBackoff::new(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(70)),
+            Duration::from_millis(300),
+        );
+        let ctx = |elapsed| BackoffContext {
+            iterations: 0,
+            previous: None,
+            elapsed: Duration::from_millis(elapsed),
+        };
+
+        assert_eq!(backoff.next_backoff(&ctx(0)), Some(Duration::from_millis(70)));
+        assert_eq!(backoff.next_backoff(&ctx(280)), Some(Duration::from_millis(20)));
+        assert_eq!(backoff.next_backoff(&ctx(300)), None);
+        assert_eq!(backoff.next_backoff(&ctx(310)), None);
+    }
+
+    #[test]
+    fn test_hinted_backoff() {
+        let mut backoff = HintedBackoff::new(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1)),
+            |error: &Option<u64>| error.map(Duration::from_secs),
+        );
+        let ctx = BackoffContext {
+            iterations: 0,
+            previous: None,
+            elapsed: Duration::ZERO,
+        };
+
+        assert_eq!(backoff.next_backoff_for(&Some(30), &ctx), Some(Duration::from_secs(30)));
+        assert_eq!(backoff.next_backoff_for(&None, &ctx), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn text_default_exponential() {
+        let mut backoff = ExponentialBackoff::default();
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(0));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(25));
+        assert_eq!(backoff.backoff_period(2), Duration::from_millis(56));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(95));
+    }
+
+    #[test]
+    fn test_exponential_builder() {
+        let mut backoff = ExponentialBackoff::builder().build();
+        let schedule: Vec<_> = (0..5).map(|iterations| backoff.backoff_period(iterations)).collect();
+        assert_eq!(schedule, [0, 100, 200, 400, 800].map(Duration::from_millis));
+
+        let mut backoff = ExponentialBackoff::builder()
+            .initial_interval(Duration::from_millis(250))
+            .multiplier(2.0)
+            .max_interval(Duration::from_secs(1))
+            .build();
+        let schedule: Vec<_> = (0..5).map(|iterations| backoff.backoff_period(iterations)).collect();
+        assert_eq!(schedule, [0, 250, 500, 1000, 1000].map(Duration::from_millis));
+
+        let mut backoff = ExponentialBackoff::builder()
+            .initial_interval(Duration::from_secs(1))
+            .multiplier(1.5)
+            .build();
+        let schedule: Vec<_> = (0..5).map(|iterations| backoff.backoff_period(iterations)).collect();
+        assert_eq!(schedule, [0, 1000, 1500, 2250, 3375].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn test_exponential_with_base() {
+        let mut backoff = ExponentialBackoff::with_base(10.0);
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(0));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(900));
+        assert_eq!(backoff.backoff_period(2), Duration::from_millis(9900));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(99900));
+    }
+
+    #[test]
+    fn test_exponential_first_attempt_delay() {
+        let mut backoff = ExponentialBackoff::default().first_attempt_delay(true);
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(25));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(56));
+
+        let mut backoff = ExponentialBackoff::default().first_attempt_delay(false);
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(0));
+    }
+
+    #[test]
+    fn test_exponential_with_initial() {
+        let mut backoff = ExponentialBackoff::with_base(2.0).with_initial(Duration::from_millis(50));
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(50));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(100));
+        assert_eq!(backoff.backoff_period(2), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_exponential_with_max() {
+        let mut backoff = ExponentialBackoff::with_base(10.0).with_max(Duration::from_secs(1));
+
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(900));
+        assert_eq!(backoff.backoff_period(2), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(3), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_exponential_with_max_elapsed() {
+        let mut backoff = ExponentialBackoff::default().with_max_elapsed(Duration::from_millis(10));
+        let mut ctx = BackoffContext {
+            iterations: 1,
+            previous: None,
+            elapsed: Duration::from_millis(5),
+        };
+
+        assert_eq!(backoff.next_backoff(&ctx), Some(Duration::from_millis(25)));
+
+        ctx.elapsed = Duration::from_millis(10);
+        assert_eq!(backoff.next_backoff(&ctx), None);
+    }
+
+    #[test]
+    fn test_linear() {
+        let mut backoff = LinearBackoff::new(Duration::from_millis(500));
+
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(500));
+        assert_eq!(backoff.backoff_period(2), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(4), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_linear_with_start() {
+        let mut backoff = LinearBackoff::with_start(Duration::from_secs(1), Duration::from_millis(250));
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(1250));
+        assert_eq!(backoff.backoff_period(4), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_linear_with_max() {
+        let mut backoff = LinearBackoff::new(Duration::from_millis(500)).with_max(Duration::from_secs(1));
+
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(500));
+        assert_eq!(backoff.backoff_period(2), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(3), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_linear_saturates() {
+        let mut backoff = LinearBackoff::new(Duration::from_secs(u64::MAX / 2));
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::MAX);
+
+        let mut backoff = LinearBackoff::with_start(Duration::MAX, Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(1), Duration::MAX);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_linear_retry() {
+        let attempts = core::cell::Cell::new(0);
+        let result = crate::retry_if(
+            LinearBackoff::new(Duration::from_millis(1)),
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(attempts.get())
+            },
+            |_, iterations| iterations + 1 < 3,
+        );
+
+        assert_eq!(result, Err(3));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn test_iterator_exhausted() {
+        let schedule = [1, 5].map(Duration::from_secs);
+        let ctx = |iterations| BackoffContext {
+            iterations,
+            previous: None,
+            elapsed: Duration::ZERO,
+        };
+
+        let mut backoff = IteratorBackoff::new(schedule);
+        assert_eq!(backoff.next_backoff(&ctx(0)), Some(Duration::from_secs(1)));
+        assert_eq!(backoff.next_backoff(&ctx(1)), Some(Duration::from_secs(5)));
+        assert_eq!(backoff.next_backoff(&ctx(2)), Some(Duration::from_secs(5)));
+
+        let mut backoff = IteratorBackoff::new(schedule).on_exhausted(OnExhausted::Fixed(Duration::from_secs(60)));
+        backoff.backoff_period(0);
+        backoff.backoff_period(1);
+        assert_eq!(backoff.next_backoff(&ctx(2)), Some(Duration::from_secs(60)));
+        assert_eq!(backoff.next_backoff(&ctx(3)), Some(Duration::from_secs(60)));
+
+        let mut backoff = IteratorBackoff::new(schedule).on_exhausted(OnExhausted::GiveUp);
+        backoff.backoff_period(0);
+        backoff.backoff_period(1);
+        assert_eq!(backoff.next_backoff(&ctx(2)), None);
+        assert_eq!(backoff.backoff_period(3), Duration::from_secs(5));
+
+        let mut backoff = IteratorBackoff::new(core::iter::empty());
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_iterator_minimum() {
+        let schedule = [10, 500].map(Duration::from_millis);
+        let iterator = IteratorBackoff::new(schedule).on_exhausted(OnExhausted::GiveUp);
+        let mut backoff = MinimumBackoff::new(iterator, Duration::from_millis(100));
+        let ctx = BackoffContext {
+            iterations: 0,
+            previous: None,
+            elapsed: Duration::ZERO,
+        };
+
+        assert_eq!(backoff.next_backoff(&ctx), Some(Duration::from_millis(100)));
+        assert_eq!(backoff.next_backoff(&ctx), Some(Duration::from_millis(500)));
+        assert_eq!(backoff.next_backoff(&ctx), None);
+    }
+
+    #[test]
+    fn test_polynomial() {
+        let mut quadratic = PolynomialBackoff::new(Duration::from_millis(10), 2);
+        let schedule: Vec<_> = (0..6).map(|iterations| quadratic.backoff_period(iterations)).collect();
+        assert_eq!(schedule, [0, 10, 40, 90, 160, 250].map(Duration::from_millis));
+
+        let mut cubic = PolynomialBackoff::new(Duration::from_millis(10), 3);
+        let schedule: Vec<_> = (0..6).map(|iterations| cubic.backoff_period(iterations)).collect();
+        assert_eq!(schedule, [0, 10, 80, 270, 640, 1250].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn test_polynomial_saturates() {
+        let mut backoff = PolynomialBackoff::new(Duration::from_millis(10), 3);
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::MAX);
+
+        let mut backoff = PolynomialBackoff::new(Duration::from_secs(u64::MAX / 2), 2);
+        assert_eq!(backoff.backoff_period(2), Duration::MAX);
+
+        let mut backoff = PolynomialBackoff::new(Duration::ZERO, 3);
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::ZERO);
+
+        let mut backoff = PolynomialBackoff::new(Duration::from_millis(10), 2).with_max(Duration::from_millis(100));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(90));
+        assert_eq!(backoff.backoff_period(4), Duration::from_millis(100));
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_exact_exponential() {
+        let mut doubling = ExactExponentialBackoff::new(Duration::from_millis(100), 2);
+        let schedule: Vec<_> = (0..6).map(|iterations| doubling.backoff_period(iterations)).collect();
+        assert_eq!(schedule, [100, 200, 400, 800, 1600, 3200].map(Duration::from_millis));
+
+        let mut tripling = ExactExponentialBackoff::new(Duration::from_millis(1), 3);
+        let schedule: Vec<_> = (0..5).map(|iterations| tripling.backoff_period(iterations)).collect();
+        assert_eq!(schedule, [1, 3, 9, 27, 81].map(Duration::from_millis));
+
+        // 25ms and 56.25ms exactly, where the float formula truncates.
+        let mut backoff = ExactExponentialBackoff::with_ratio(Duration::from_millis(25), 3, 2);
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(25));
+        assert_eq!(backoff.backoff_period(2), Duration::from_micros(56_250));
+
+        let mut constant = ExactExponentialBackoff::new(Duration::from_millis(100), 1);
+        assert_eq!(constant.backoff_period(u32::MAX), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_exact_exponential_saturates() {
+        let mut backoff = ExactExponentialBackoff::new(Duration::from_millis(100), 2);
+        assert_eq!(backoff.backoff_period(200), Duration::MAX);
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::MAX);
+
+        let mut backoff = ExactExponentialBackoff::with_ratio(Duration::from_nanos(1), 3, 2);
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::from_nanos(1));
+
+        let mut backoff = ExactExponentialBackoff::new(Duration::from_millis(100), 2).with_max(Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(800));
+        assert_eq!(backoff.backoff_period(4), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(u32::MAX), Duration::from_secs(1));
+
+        let mut backoff = ExactExponentialBackoff::new(Duration::from_secs(5), 2).with_max(Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(0), Duration::from_secs(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "ExactExponentialBackoff multiplier must be at least one")]
+    fn test_exact_exponential_shrinking() {
+        ExactExponentialBackoff::with_ratio(Duration::from_secs(1), 1, 2);
+    }
+
+    #[test]
+    fn test_exponential_invalid_base() {
+        for base in [0.0, 0.5, 1.0, -2.0, f32::NAN, f32::INFINITY] {
+            let error = ExponentialBackoff::try_with_base(base).unwrap_err();
+            assert!(error.base() == base || base.is_nan());
+        }
+
+        let mut backoff = ExponentialBackoff::try_with_base(1.5).unwrap();
+        assert!(backoff.backoff_period(1) > Duration::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "ExponentialBackoff base must be a finite number greater than 1, got 0.5")]
+    fn test_exponential_with_base_below_one() {
+        ExponentialBackoff::with_base(0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "ExponentialBackoff base must be a finite number greater than 1, got NaN")]
+    fn test_exponential_with_base_nan() {
+        ExponentialBackoff::with_base(f32::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "ExponentialBackoff base must be a finite number greater than 1, got 1")]
+    fn test_exponential_builder_multiplier_of_one() {
+        ExponentialBackoff::builder().multiplier(1.0).build();
+    }
+
+    #[test]
+    fn test_stagger() {
+        let window = Duration::from_secs(10);
+        let schedule = |index| {
+            let mut backoff = StaggerBackoff::new(ExponentialBackoff::with_base(2.0), index, 4, window);
+            (0..4).map(|iterations| backoff.backoff_period(iterations)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(schedule(0), [0, 100, 300, 700].map(Duration::from_millis));
+        assert_eq!(schedule(1), [2500, 2600, 2800, 3200].map(Duration::from_millis));
+        assert_eq!(schedule(3), schedule(3));
+
+        let mut backoff = StaggerBackoff::new(ConstantBackoff::new(Duration::MAX), 1, 2, window);
+        assert_eq!(backoff.backoff_period(0), Duration::MAX);
+    }
+
+    #[test]
+    fn test_stagger_from_key() {
+        let window = Duration::from_secs(10);
+        let offset = |key| StaggerBackoff::from_key(ImmediateBackoff, &key, window).offset();
+
+        assert_eq!(offset("replica-1"), offset("replica-1"));
+        assert_ne!(offset("replica-1"), offset("replica-2"));
+        assert!(offset("replica-1") < window);
+
+        // The offsets are fixed, they don't change between Rust releases.
+        assert_eq!(offset("replica-1"), Duration::from_nanos(7_548_765_474));
+
+        let mut backoff = CappedBackoff::new(StaggerBackoff::from_key(ImmediateBackoff, &"replica-1", window), window);
+        assert_eq!(backoff.backoff_period(0), offset("replica-1"));
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        use core::hash::Hasher;
+
+        let mut hasher = Fnv1aHasher::default();
+        assert_eq!(hasher.finish(), 0xcbf2_9ce4_8422_2325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    #[should_panic(expected = "StaggerBackoff index must be less than the total")]
+    fn test_stagger_index_out_of_range() {
+        StaggerBackoff::new(ImmediateBackoff, 4, 4, Duration::from_secs(1));
     }
 
     #[test]
@@ -106,9 +2935,346 @@ mod tests {
         assert_eq!(ImmediateBackoff.backoff_period(0), Duration::from_millis(0));
     }
 
+    #[test]
+    fn test_constant() {
+        const BACKOFF: ConstantBackoff = ConstantBackoff::from_millis(250);
+
+        let mut backoff = BACKOFF;
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(250));
+        assert_eq!(backoff.backoff_period(100), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_duration() {
+        let mut backoff = Duration::from_secs(2);
+
+        assert_eq!(Backoff::backoff_period(&mut backoff, 0), Duration::from_secs(2));
+        assert_eq!(Backoff::backoff_period(&mut backoff, 100), Duration::from_secs(2));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_constant_retry() {
+        const BACKOFF: ConstantBackoff = ConstantBackoff::from_millis(1);
+
+        let attempts = core::cell::Cell::new(0);
+        let result = crate::retry_if(
+            BACKOFF,
+            || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 3 {
+                    Err(attempts.get())
+                } else {
+                    Ok(attempts.get())
+                }
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_random_within_range() {
+        let (min, max) = (Duration::from_millis(100), Duration::from_millis(200));
+        let mut backoff = RandomBackoff::new(min, max).with_seed(7);
+
+        for iterations in 0..1000 {
+            let duration = backoff.backoff_period(iterations);
+            assert!(duration >= min && duration <= max);
+        }
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_random_is_seeded() {
+        let (min, max) = (Duration::from_millis(100), Duration::from_millis(200));
+        let mut first = RandomBackoff::new(min, max).with_seed(7);
+        let mut second = RandomBackoff::new(min, max).with_seed(7);
+
+        for iterations in 0..10 {
+            assert_eq!(first.backoff_period(iterations), second.backoff_period(iterations));
+        }
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_random_with_rng() {
+        let (min, max) = (Duration::from_millis(100), Duration::from_millis(200));
+        let mut backoff = RandomBackoff::with_rng(min, max, SequenceRng(&[0.0, 0.25, 0.5, 0.999999]));
+        let schedule: Vec<_> = (0..4).map(|iterations| backoff.backoff_period(iterations)).collect();
+
+        assert_eq!(
+            schedule,
+            [
+                Duration::from_millis(100),
+                Duration::from_millis(125),
+                Duration::from_millis(150),
+                Duration::from_nanos(199_999_900),
+            ]
+        );
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_random_varies() {
+        let (min, max) = (Duration::from_secs(2), Duration::from_secs(5));
+        let mut backoff = RandomBackoff::new(min, max);
+        let samples: Vec<_> = (0..10).map(|iterations| backoff.backoff_period(iterations)).collect();
+
+        assert!(samples.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_random_with_minimum() {
+        let (min, max) = (Duration::from_millis(100), Duration::from_millis(200));
+        let floor = Duration::from_millis(150);
+        let mut backoff = MinimumBackoff::new(RandomBackoff::new(min, max).with_seed(7), floor);
+
+        for iterations in 0..100 {
+            let duration = backoff.backoff_period(iterations);
+            assert!(duration >= floor && duration <= max);
+        }
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_random_degenerate() {
+        let mut backoff = RandomBackoff::new(Duration::from_secs(1), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(0), Duration::from_secs(1));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    #[should_panic(expected = "RandomBackoff minimum must not exceed its maximum")]
+    fn test_random_min_above_max() {
+        RandomBackoff::new(Duration::from_secs(2), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_fn_backoff() {
+        let mut backoff = FnBackoff(|iterations| Duration::from_millis(50 * iterations as u64));
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(0));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_boxed() {
+        let mut backoff: Box<dyn Backoff> = Box::new(ExponentialBackoff::with_base(10.0));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(900));
+    }
+
     #[test]
     fn test_minimum() {
         let mut backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1));
         assert_eq!(backoff.backoff_period(0), Duration::from_secs(1));
     }
+
+    #[test]
+    fn test_capped() {
+        let mut backoff = CappedBackoff::new(ExponentialBackoff::with_base(10.0), Duration::from_secs(5));
+
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(900));
+        assert_eq!(backoff.first_capped_iteration(), None);
+
+        assert_eq!(backoff.backoff_period(2), Duration::from_secs(5));
+        assert_eq!(backoff.backoff_period(3), Duration::from_secs(5));
+        assert_eq!(backoff.first_capped_iteration(), Some(2));
+    }
+
+    #[test]
+    fn test_clamp() {
+        let (min, max) = (Duration::from_millis(100), Duration::from_secs(5));
+        let mut backoff = ClampBackoff::new(ExponentialBackoff::with_base(10.0), min, max);
+
+        assert_eq!(backoff.backoff_period(0), min);
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(900));
+        assert_eq!(backoff.backoff_period(2), max);
+        assert_eq!(backoff.backoff_period(u32::MAX), max);
+    }
+
+    #[test]
+    #[should_panic(expected = "ClampBackoff minimum must not exceed its maximum")]
+    fn test_clamp_invalid() {
+        ClampBackoff::new(ImmediateBackoff, Duration::from_secs(2), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_min_max_composition() {
+        let (min, max) = (Duration::from_millis(100), Duration::from_secs(5));
+        let mut capped_minimum = CappedBackoff::new(MinimumBackoff::new(ExponentialBackoff::with_base(10.0), min), max);
+        let mut minimum_capped = MinimumBackoff::new(CappedBackoff::new(ExponentialBackoff::with_base(10.0), max), min);
+
+        // With a minimum below the maximum the order doesn't matter.
+        for iterations in [0, 1, 2, u32::MAX] {
+            let expected = ExponentialBackoff::with_base(10.0).backoff_period(iterations).clamp(min, max);
+            assert_eq!(capped_minimum.backoff_period(iterations), expected);
+            assert_eq!(minimum_capped.backoff_period(iterations), expected);
+        }
+
+        // Otherwise the outermost wrapper wins.
+        let (min, max) = (Duration::from_secs(10), Duration::from_secs(5));
+        let mut capped_minimum = CappedBackoff::new(MinimumBackoff::new(ImmediateBackoff, min), max);
+        let mut minimum_capped = MinimumBackoff::new(CappedBackoff::new(ImmediateBackoff, max), min);
+
+        assert_eq!(capped_minimum.backoff_period(0), max);
+        assert_eq!(minimum_capped.backoff_period(0), min);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut backoff = CappedBackoff::new(ExponentialBackoff::with_base(10.0), Duration::from_secs(5));
+        backoff.backoff_period(2);
+        assert_eq!(backoff.first_capped_iteration(), Some(2));
+
+        backoff.reset();
+        assert_eq!(backoff.first_capped_iteration(), None);
+
+        let mut backoff = FreezableBackoff::new(ExponentialBackoff::with_base(10.0));
+        backoff.backoff_period(2);
+        backoff.freeze();
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(9900));
+
+        backoff.reset();
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(900));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(900));
+    }
+
+    #[test]
+    fn test_max_explain() {
+        let constant = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1));
+        let mut backoff = MaxBackoff::new(ExponentialBackoff::with_base(10.0), constant);
+
+        let explanation = backoff.explain(1);
+        assert_eq!(explanation.delay, Duration::from_secs(1));
+        assert!(explanation.source.contains("MinimumBackoff"));
+
+        let explanation = backoff.explain(2);
+        assert_eq!(explanation.delay, Duration::from_millis(9900));
+        assert!(explanation.source.contains("ExponentialBackoff"));
+    }
+
+    #[test]
+    fn test_minimum_explain() {
+        let mut backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1));
+
+        let explanation = backoff.explain(0);
+        assert_eq!(explanation.delay, Duration::from_secs(1));
+        assert!(explanation.source.contains("MinimumBackoff"));
+    }
+
+    #[test]
+    fn test_phased() {
+        let first = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1));
+        let mut backoff = PhasedBackoff::new(first, 3, ExponentialBackoff::with_base(10.0));
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(2), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(0));
+        assert_eq!(backoff.backoff_period(4), Duration::from_millis(900));
+    }
+
+    #[test]
+    fn test_combinators() {
+        let mut backoff = ExponentialBackoff::with_base(10.0)
+            .scale(2.0)
+            .map(|delay, _| delay.min(Duration::from_secs(1)));
+
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+        assert_eq!(backoff.backoff_period(1), Duration::from_secs(1));
+
+        // Capping before scaling doubles the cap instead.
+        let mut backoff = ExponentialBackoff::with_base(10.0)
+            .map(|delay, _| delay.min(Duration::from_secs(1)))
+            .scale(2.0);
+
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(1800));
+        assert_eq!(backoff.backoff_period(2), Duration::from_secs(2));
+
+        let mut backoff = ImmediateBackoff
+            .plus(Duration::from_millis(10))
+            .map(|delay, iterations| delay * iterations);
+
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(30));
+
+        let mut backoff = MinimumBackoff::new(ImmediateBackoff.plus(Duration::from_millis(10)), Duration::from_secs(1))
+            .plus(Duration::MAX);
+
+        assert_eq!(backoff.backoff_period(0), Duration::MAX);
+    }
+
+    #[test]
+    fn test_combinators_next_backoff() {
+        let inner = IteratorBackoff::new([Duration::from_millis(10)]).on_exhausted(OnExhausted::GiveUp);
+        let mut backoff = inner.plus(Duration::from_millis(5)).map(|delay, iterations| delay * (iterations + 1));
+        let ctx = |iterations| BackoffContext {
+            iterations,
+            previous: None,
+            elapsed: Duration::ZERO,
+        };
+
+        assert_eq!(backoff.next_backoff(&ctx(1)), Some(Duration::from_millis(30)));
+        assert_eq!(backoff.next_backoff(&ctx(2)), None);
+    }
+
+    #[test]
+    fn test_then() {
+        let mut backoff = ImmediateBackoff.then(3, ExponentialBackoff::default());
+
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+        assert_eq!(backoff.backoff_period(2), Duration::ZERO);
+        // The exponential backoff starts from its own first iteration.
+        assert_eq!(backoff.backoff_period(3), Duration::ZERO);
+        assert_eq!(backoff.backoff_period(4), Duration::from_millis(25));
+        assert_eq!(backoff.backoff_period(5), Duration::from_millis(56));
+    }
+
+    #[test]
+    fn test_then_composes() {
+        let inner = ImmediateBackoff.then(2, ExponentialBackoff::with_base(10.0));
+        let mut backoff = MinimumBackoff::new(inner, Duration::from_millis(100));
+
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(100));
+        assert_eq!(backoff.backoff_period(3), Duration::from_millis(900));
+
+        let inner = ConstantBackoff::from_millis(10).then(1, ConstantBackoff::from_millis(100));
+        let mut backoff = JitterBackoff::with_strategy(inner, SequenceRng(&[0.5, 0.5]), JitterStrategy::Full);
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(5));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_phased_context() {
+        let mut backoff = PhasedBackoff::new(ImmediateBackoff, 2, FnBackoff(|iterations| {
+            Duration::from_secs(iterations as u64)
+        }));
+        let ctx = BackoffContext {
+            iterations: 5,
+            previous: None,
+            elapsed: Duration::from_secs(0),
+        };
+
+        assert_eq!(backoff.next_backoff(&ctx), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_freezable() {
+        let mut backoff = FreezableBackoff::new(ExponentialBackoff::with_base(10.0));
+        let handle = backoff.handle();
+
+        for iterations in 0..=3 {
+            backoff.backoff_period(iterations);
+        }
+
+        handle.freeze();
+        assert_eq!(backoff.backoff_period(4), Duration::from_millis(99900));
+        assert_eq!(backoff.backoff_period(5), Duration::from_millis(99900));
+
+        handle.unfreeze();
+        assert_eq!(backoff.backoff_period(4), Duration::from_millis(999900));
+    }
 }
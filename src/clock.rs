@@ -0,0 +1,20 @@
+use std::time::Instant;
+
+/// A source of the current time used by retry functions that have a deadline,
+/// allowing tests to supply a clock that doesn't rely on real time passing.
+///
+/// Any `Fn() -> Instant` implements [Now](crate::clock::Now), so
+/// [Instant::now](std::time::Instant::now) can be passed directly.
+pub trait Now {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+impl<F> Now for F
+where
+    F: Fn() -> Instant,
+{
+    fn now(&self) -> Instant {
+        self()
+    }
+}
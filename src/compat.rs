@@ -0,0 +1,158 @@
+//! Interoperability with the strategies of the
+//! [backoff](https://crates.io/crates/backoff) crate.
+
+use std::time::Duration;
+
+use crate::backoff::{Backoff, BackoffContext};
+
+/// A [Backoff](crate::backoff::Backoff) implementation that wraps a strategy
+/// of the [backoff](https://crates.io/crates/backoff) crate, so it can be
+/// passed to the retry functions of this crate.
+///
+/// The wrapped strategy gives up by returning `None`, which is forwarded from
+/// [next_backoff](crate::backoff::Backoff::next_backoff). Resetting the
+/// adapter resets the wrapped strategy, restarting its elapsed time.
+///
+/// # Example
+/// ```
+/// # extern crate backoff_crate as backoff;
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+/// #   return Ok(()); // So our doctests pass.
+///     Err(())
+/// }
+///
+/// let backoff = BackoffCompat::new(backoff::ExponentialBackoff::default());
+/// let value = tryagain::retry(backoff, returns_err);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BackoffCompat<B> {
+    inner: B,
+    last: Option<Duration>,
+}
+
+impl<B: backoff_crate::backoff::Backoff> BackoffCompat<B> {
+    /// Wraps a strategy of the [backoff](https://crates.io/crates/backoff)
+    /// crate.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            last: None,
+        }
+    }
+
+    /// Returns the wrapped strategy.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    fn next(&mut self) -> Option<Duration> {
+        let duration = self.inner.next_backoff();
+        if duration.is_some() {
+            self.last = duration;
+        }
+        duration
+    }
+}
+
+impl<B: backoff_crate::backoff::Backoff> Backoff for BackoffCompat<B> {
+    /// Returns the last delay, or no delay, once the wrapped strategy gives up
+    /// as only [next_backoff](crate::backoff::Backoff::next_backoff) is able
+    /// to.
+    fn backoff_period(&mut self, _iterations: u32) -> Duration {
+        self.next().unwrap_or_else(|| self.last.unwrap_or(Duration::ZERO))
+    }
+
+    fn next_backoff(&mut self, _ctx: &BackoffContext) -> Option<Duration> {
+        self.next()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry_if;
+    use std::cell::Cell;
+
+    struct Countdown {
+        remaining: u32,
+        resets: u32,
+    }
+
+    impl backoff_crate::backoff::Backoff for Countdown {
+        fn reset(&mut self) {
+            self.resets += 1;
+        }
+
+        fn next_backoff(&mut self) -> Option<Duration> {
+            let remaining = self.remaining.checked_sub(1)?;
+            self.remaining = remaining;
+            Some(Duration::from_millis(1))
+        }
+    }
+
+    #[test]
+    fn test_forwards_some() {
+        let backoff = BackoffCompat::new(backoff_crate::backoff::Constant::new(Duration::from_millis(1)));
+        let attempts = Cell::new(0);
+
+        let result = retry_if(
+            backoff,
+            || {
+                attempts.set(attempts.get() + 1);
+
+                if attempts.get() < 3 {
+                    Err(())
+                } else {
+                    Ok(attempts.get())
+                }
+            },
+            |_, _| true,
+        );
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn test_forwards_none() {
+        let mut backoff = BackoffCompat::new(Countdown {
+            remaining: 2,
+            resets: 0,
+        });
+        let attempts = Cell::new(0);
+
+        let result = retry_if(
+            &mut backoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(())
+            },
+            |_, _| true,
+        );
+        assert_eq!(result, Err(()));
+        assert_eq!(attempts.get(), 3);
+        assert_eq!(backoff.into_inner().resets, 1);
+    }
+
+    #[test]
+    fn test_backoff_period_after_giving_up() {
+        let mut backoff = BackoffCompat::new(Countdown {
+            remaining: 1,
+            resets: 0,
+        });
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(1));
+        assert_eq!(backoff.backoff_period(1), Duration::from_millis(1));
+
+        let ctx = BackoffContext {
+            iterations: 2,
+            previous: None,
+            elapsed: Duration::ZERO,
+        };
+        assert_eq!(backoff.next_backoff(&ctx), None);
+    }
+}
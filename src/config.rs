@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Backoff, ConstantBackoff, ExponentialBackoff, ImmediateBackoff, MinimumBackoff};
+
+/// A serializable description of one of the built-in
+/// [Backoff](crate::backoff::Backoff) implementations, allowing retry behavior
+/// to be loaded from configuration files.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// let config: BackoffConfig = serde_json::from_str(r#"{ "type": "exponential", "base": 2.0 }"#).unwrap();
+/// let backoff = config.build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackoffConfig {
+    /// Builds an [ImmediateBackoff](crate::backoff::ImmediateBackoff).
+    Immediate,
+    /// Builds an [ExponentialBackoff](crate::backoff::ExponentialBackoff).
+    Exponential {
+        /// A finite number greater than `1.0`, other values are rejected when
+        /// deserializing.
+        #[serde(deserialize_with = "crate::backoff::deserialize_base")]
+        base: f32,
+        #[serde(default)]
+        initial: Option<Duration>,
+        #[serde(default)]
+        max: Option<Duration>,
+    },
+    /// Builds a [ConstantBackoff](crate::backoff::ConstantBackoff) that always
+    /// waits the same number of milliseconds.
+    Constant { millis: u64 },
+    /// Builds a [MinimumBackoff](crate::backoff::MinimumBackoff) around
+    /// another configured backoff.
+    Minimum {
+        inner: Box<BackoffConfig>,
+        min: Duration,
+    },
+}
+
+impl BackoffConfig {
+    /// Creates the [Backoff](crate::backoff::Backoff) described by this
+    /// configuration.
+    pub fn build(&self) -> Box<dyn Backoff + Send> {
+        match self {
+            Self::Immediate => Box::new(ImmediateBackoff),
+            Self::Exponential {
+                base,
+                initial,
+                max,
+            } => {
+                let mut backoff = ExponentialBackoff::with_base(*base);
+
+                if let Some(initial) = initial {
+                    backoff = backoff.with_initial(*initial);
+                }
+
+                if let Some(max) = max {
+                    backoff = backoff.with_max(*max);
+                }
+
+                Box::new(backoff)
+            }
+            Self::Constant { millis } => Box::new(ConstantBackoff::from_millis(*millis)),
+            Self::Minimum { inner, min } => Box::new(MinimumBackoff::new(inner.build(), *min)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CappedBackoff, LinearBackoff};
+
+    #[test]
+    fn test_round_trip() {
+        let config = BackoffConfig::Minimum {
+            inner: Box::new(BackoffConfig::Exponential {
+                base: 10.0,
+                initial: None,
+                max: Some(Duration::from_secs(5)),
+            }),
+            min: Duration::from_secs(1),
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: BackoffConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, config);
+
+        let mut backoff = parsed.build();
+        assert_eq!(backoff.backoff_period(0), Duration::from_secs(1));
+        assert_eq!(backoff.backoff_period(2), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_constant_from_json() {
+        let config: BackoffConfig = serde_json::from_str(r#"{ "type": "constant", "millis": 250 }"#).unwrap();
+        let mut backoff = config.build();
+
+        assert_eq!(backoff.backoff_period(0), Duration::from_millis(250));
+        assert_eq!(backoff.backoff_period(7), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_backoff_round_trip() {
+        let exponential = ExponentialBackoff::new(Duration::from_millis(200), Duration::from_secs(30), 2.0)
+            .with_max_elapsed(Duration::from_secs(60));
+        let backoff = MinimumBackoff::new(exponential, Duration::from_millis(100));
+
+        let json = serde_json::to_string(&backoff).unwrap();
+        let mut parsed: MinimumBackoff<ExponentialBackoff> = serde_json::from_str(&json).unwrap();
+        let mut backoff = backoff;
+
+        for iterations in 0..20 {
+            assert_eq!(parsed.backoff_period(iterations), backoff.backoff_period(iterations));
+        }
+
+        let backoff = CappedBackoff::new(LinearBackoff::new(Duration::from_secs(1)), Duration::from_secs(5));
+        let json = serde_json::to_string(&backoff).unwrap();
+        let mut parsed: CappedBackoff<LinearBackoff> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.backoff_period(10), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_exponential_from_json() {
+        let json = r#"{ "base": 2.0, "max": { "secs": 1, "nanos": 0 } }"#;
+        let mut parsed: ExponentialBackoff = serde_json::from_str(json).unwrap();
+        let mut backoff = ExponentialBackoff::with_base(2.0).with_max(Duration::from_secs(1));
+
+        for iterations in 0..10 {
+            assert_eq!(parsed.backoff_period(iterations), backoff.backoff_period(iterations));
+        }
+
+        let parsed: ConstantBackoff = serde_json::from_str(r#"{ "period": { "secs": 2, "nanos": 0 } }"#).unwrap();
+        assert_eq!(parsed, ConstantBackoff::from_millis(2000));
+    }
+
+    #[test]
+    fn test_exponential_invalid_base_from_json() {
+        let error = serde_json::from_str::<ExponentialBackoff>(r#"{ "base": 0.5 }"#).unwrap_err();
+        assert!(error.to_string().contains("greater than 1"));
+    }
+
+    #[test]
+    fn test_config_invalid_base_from_json() {
+        let error = serde_json::from_str::<BackoffConfig>(r#"{ "type": "exponential", "base": 0.5 }"#).unwrap_err();
+        assert!(error.to_string().contains("greater than 1"));
+    }
+}
@@ -1,15 +1,36 @@
 //! An async version of the [retry](crate::sync::retry) and
 //! [retry_if](crate::sync::retry_if) function along with
-//! [RetryFuture](crate::future::RetryFuture) used to implement them.
+//! [RetryFuture](crate::future::RetryFuture) used to implement them, and
+//! [retry_stream](crate::future::retry_stream) for observing every attempt.
 
 use std::{
+    fmt,
     future::Future,
     pin::Pin,
-    task::{Context, Poll},
-    time::Instant,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
 };
 
-use crate::Backoff;
+#[cfg(not(all(feature = "runtime-wasm", target_arch = "wasm32")))]
+use std::time::Instant;
+#[cfg(all(feature = "runtime-wasm", target_arch = "wasm32"))]
+use web_time::Instant;
+
+use futures_core::Stream;
+
+use crate::{
+    backoff::Schedule,
+    timer::{self, Timer},
+    sync::History,
+    trace, Backoff, BackoffContext, BudgetBackoff, HintedBackoff, HistoryLimit, RetryDelayHint, RetryError, RetryState, RetryStats,
+    Retryable,
+};
+
+pub use crate::state::{NoNotify, Notify};
 
 /// Retries the provided function if it returns an error whenever the backoff
 /// allows. The first call resulting in success will have it's value returned
@@ -34,12 +55,356 @@ pub fn retry<B, F, T, E, Fut>(
 ) -> RetryFuture<F, Fut, impl Fn(&E, u32) -> bool, B>
 where
     B: Backoff,
-    F: Fn() -> Fut,
+    F: FnMut() -> Fut,
     Fut: Future<Output = Result<T, E>>,
 {
     retry_if(backoff, func, |_, _| true)
 }
 
+/// Calls the provided function at most `n` times, retrying whenever the
+/// backoff allows, and resolves to the error of the final attempt if none of
+/// them succeeded. The error is also returned if the backoff gives up first.
+///
+/// `n` is the total number of attempts including the first one, not the
+/// number of retries, the same as [retry_n](crate::sync::retry_n). An `n` of
+/// `0` is treated as `1`, the function is always called at least once.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// // Calls returns_err 5 times.
+/// let result = tryagain::future::retry_n(ExponentialBackoff::default(), 5, returns_err).await;
+/// # };
+/// ```
+pub fn retry_n<B, F, T, E, Fut>(
+    backoff: B,
+    n: u32,
+    func: F,
+) -> RetryFuture<F, Fut, impl Fn(&E, u32) -> bool, B>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    // The iterations start at one after the first failed attempt.
+    retry_if(backoff, func, move |_, iterations| iterations < n)
+}
+
+/// The same as [retry](crate::future::retry) but the function is passed the
+/// zero-based number of the attempt it is making, which can be used for
+/// idempotency keys or logging.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn send(attempt: u32) -> Result<(), ()> {
+///     println!("sending, attempt {}", attempt);
+///     Err(())
+/// }
+///
+/// let value = tryagain::future::retry_enumerated(ExponentialBackoff::default(), send).await;
+/// # };
+/// ```
+pub fn retry_enumerated<B, F, T, E, Fut>(
+    backoff: B,
+    mut func: F,
+) -> RetryFuture<impl FnMut() -> Fut, Fut, impl Fn(&E, u32) -> bool, B>
+where
+    B: Backoff,
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 0;
+
+    retry(backoff, move || {
+        let future = func(attempt);
+        attempt += 1;
+        future
+    })
+}
+
+/// Retries the provided function while it resolves to `None` whenever the
+/// backoff allows, resolving to the value of the first `Some`, or `None` if
+/// the backoff gave up.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn poll_job() -> Option<u32> {
+///     None
+/// }
+///
+/// let value = tryagain::future::retry_option(ExponentialBackoff::default(), poll_job).await;
+/// # };
+/// ```
+pub fn retry_option<B, F, T, Fut>(backoff: B, mut func: F) -> impl Future<Output = Option<T>>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    let future = retry(backoff, move || {
+        let future = func();
+        async move { future.await.ok_or(()) }
+    });
+
+    async move { future.await.ok() }
+}
+
+/// Retries the provided function while it resolves to a value that isn't
+/// `done` yet whenever the backoff allows, resolving to the first value for
+/// which `done` returns true. Errors aren't retried and are returned
+/// immediately.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn job_progress() -> Result<u32, ()> {
+///     Ok(100)
+/// }
+///
+/// let progress = tryagain::future::retry_until_value(
+///     ExponentialBackoff::default(),
+///     job_progress,
+///     |progress| *progress == 100,
+/// ).await;
+/// # };
+/// ```
+///
+/// If the backoff gives up the last value is resolved to even though it isn't
+/// done yet, pass it to `done` again if the difference matters.
+pub fn retry_until_value<B, F, D, T, E, Fut>(
+    backoff: B,
+    mut func: F,
+    done: D,
+) -> impl Future<Output = Result<T, E>>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    D: Fn(&T) -> bool,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let done = Arc::new(done);
+    let future = retry_if(
+        backoff,
+        move || {
+            let future = func();
+            let done = done.clone();
+            async move {
+                match future.await {
+                    Ok(value) if done(&value) => Ok(value),
+                    // Values that aren't done are retried, errors aren't.
+                    Ok(value) => Err(Ok(value)),
+                    Err(e) => Err(Err(e)),
+                }
+            }
+        },
+        |last: &Result<T, E>, _| last.is_ok(),
+    );
+
+    async move { future.await.or_else(|last| last) }
+}
+
+/// A [Backoff](crate::backoff::Backoff) whose duration is computed
+/// asynchronously, for strategies that need to ask something else how long to
+/// wait, such as a rate limiting service. Used by
+/// [retry_async_backoff](crate::future::retry_async_backoff).
+///
+/// Every [Backoff](crate::backoff::Backoff) is also an
+/// [AsyncBackoff](crate::future::AsyncBackoff) that resolves immediately to
+/// its [next_backoff](crate::backoff::Backoff::next_backoff), giving up when it
+/// does, and is [reset](crate::backoff::Backoff::reset) when restarted.
+///
+/// # Example
+/// ```
+/// # use tryagain::{future::AsyncBackoff, BackoffContext};
+/// # use std::{future::Future, time::Duration};
+/// struct RateLimited;
+///
+/// async fn ask_rate_limiter() -> Option<Duration> {
+///     Some(Duration::from_millis(250))
+/// }
+///
+/// impl AsyncBackoff for RateLimited {
+///     fn next_delay(&mut self, _ctx: &BackoffContext) -> impl Future<Output = Option<Duration>> {
+///         ask_rate_limiter()
+///     }
+/// }
+/// ```
+pub trait AsyncBackoff {
+    /// Resolves to the duration that must be waited until the function is
+    /// tried again, or `None` if the function shouldn't be retried anymore.
+    fn next_delay(&mut self, ctx: &BackoffContext) -> impl Future<Output = Option<Duration>>;
+
+    /// Forgets any state kept from a previous retry operation, called before
+    /// the first attempt.
+    ///
+    /// By default it does nothing.
+    fn restart(&mut self) {}
+}
+
+impl<B: Backoff> AsyncBackoff for B {
+    fn next_delay(&mut self, ctx: &BackoffContext) -> impl Future<Output = Option<Duration>> {
+        std::future::ready(self.next_backoff(ctx))
+    }
+
+    fn restart(&mut self) {
+        self.reset();
+    }
+}
+
+/// Retries the provided function if it returns an error, awaiting the
+/// [AsyncBackoff](crate::future::AsyncBackoff) for the duration to wait
+/// between attempts. If the backoff gives up the last error is returned.
+///
+/// # Example
+/// ```
+/// # use tryagain::{future::AsyncBackoff, BackoffContext};
+/// # use std::{future::Future, time::Duration};
+/// # async {
+/// struct RateLimited;
+///
+/// impl AsyncBackoff for RateLimited {
+///     fn next_delay(&mut self, _ctx: &BackoffContext) -> impl Future<Output = Option<Duration>> {
+///         async { Some(Duration::from_millis(250)) }
+///     }
+/// }
+///
+/// async fn returns_ok() -> Result<u32, ()> {
+///     Ok(42)
+/// }
+///
+/// let value = tryagain::future::retry_async_backoff(RateLimited, returns_ok).await;
+/// # assert_eq!(value, Ok(42));
+/// # };
+/// ```
+pub async fn retry_async_backoff<B, F, T, E, Fut>(mut backoff: B, mut func: F) -> Result<T, E>
+where
+    B: AsyncBackoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let start = Instant::now();
+    backoff.restart();
+    let mut schedule = Schedule::default();
+    let mut iterations = 0;
+
+    loop {
+        let result = func().await;
+        trace::attempted(None);
+
+        let e = match result {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        iterations += 1;
+        let backoff = &mut backoff;
+        let next = schedule
+            .next_async(iterations, start.elapsed(), |ctx| async move { backoff.next_delay(&ctx).await })
+            .await;
+
+        match next {
+            Some(duration) => {
+                trace::retrying(None, iterations, duration);
+                timer::sleep(duration).await;
+            }
+            None => {
+                trace::gave_up(None);
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// The same as [retry](crate::future::retry) but a permit is acquired from
+/// `semaphore` before every attempt and released once the attempt completes,
+/// so that the number of attempts in flight across all the retries sharing the
+/// semaphore is limited. The permit isn't held while waiting for the backoff.
+///
+/// If the semaphore is closed attempts are made without a permit.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::sync::Arc;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let semaphore = Arc::new(tokio::sync::Semaphore::new(2));
+/// let value = tryagain::future::retry_with_semaphore(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     semaphore.clone(),
+/// ).await;
+/// # };
+/// ```
+#[cfg(feature = "runtime-tokio")]
+pub fn retry_with_semaphore<B, F, T, E, Fut>(
+    backoff: B,
+    mut func: F,
+    semaphore: Arc<tokio::sync::Semaphore>,
+) -> impl Future<Output = Result<T, E>>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry(backoff, move || {
+        let semaphore = semaphore.clone();
+        let future = func();
+
+        async move {
+            let _permit = semaphore.acquire().await.ok();
+            future.await
+        }
+    })
+}
+
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows until `total` has elapsed since the first attempt. The final backoff
+/// period is truncated to the remaining budget, so the future resolves close
+/// to `total` rather than overshooting it by a whole period.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let result = tryagain::future::retry_for(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     Duration::from_secs(5),
+/// ).await;
+/// # };
+/// ```
+pub fn retry_for<B, F, T, E, Fut>(
+    backoff: B,
+    func: F,
+    total: Duration,
+) -> RetryFuture<F, Fut, impl Fn(&E, u32) -> bool, BudgetBackoff<B>>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry(BudgetBackoff::new(backoff, total), func)
+}
+
 /// Calls the provided function and if an error is returned it is passed to
 /// the predicate to determine if the function should be retried when the
 /// backoff function allows.
@@ -71,93 +436,2101 @@ where
 pub fn retry_if<B, F, P, T, E, Fut>(backoff: B, func: F, predicate: P) -> RetryFuture<F, Fut, P, B>
 where
     B: Backoff,
-    F: Fn() -> Fut,
+    F: FnMut() -> Fut,
     Fut: Future<Output = Result<T, E>>,
     P: Fn(&E, u32) -> bool,
 {
-    let future = func();
-
-    RetryFuture {
-        factory: func,
-        future,
-        predicate,
-        backoff,
-        paused_until: None,
-        iterations: 0,
-    }
+    RetryFuture::new(backoff, func, predicate, NoNotify)
 }
 
-#[pin_project::pin_project]
-/// A future that will retry an operation.
-pub struct RetryFuture<F, Fut, P, B> {
-    factory: F,
-    #[pin]
-    future: Fut,
-    predicate: P,
-    backoff: B,
-    paused_until: Option<Instant>,
-    iterations: u32,
+/// Retries the provided function whenever the backoff allows as long as the
+/// returned error is [retryable](crate::Retryable::is_retryable), the same as
+/// [sync::retry_retryable](crate::sync::retry_retryable).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// use std::io::{self, ErrorKind};
+///
+/// async fn read_config() -> io::Result<String> {
+///     Err(ErrorKind::NotFound.into())
+/// }
+///
+/// // A missing file isn't transient, so it isn't retried.
+/// let result = tryagain::future::retry_retryable(ExponentialBackoff::default(), read_config).await;
+/// # assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+/// # };
+/// ```
+pub fn retry_retryable<B, F, T, E, Fut>(backoff: B, func: F) -> RetryFuture<F, Fut, impl Fn(&E, u32) -> bool, B>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Retryable,
+{
+    retry_if(backoff, func, |error: &E, _| error.is_retryable())
 }
 
-impl<T, E, F, Fut, P, B> Future for RetryFuture<F, Fut, P, B>
+/// The same as [retry](crate::future::retry) but also returns
+/// [RetryStats](crate::sync::RetryStats) describing what the result cost,
+/// along with the value on success or the last error if the backoff gave up,
+/// the same as [sync::retry_with_stats](crate::sync::retry_with_stats).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_ok() -> Result<u32, ()> {
+///     Ok(42)
+/// }
+///
+/// if let Ok((value, stats)) = tryagain::future::retry_with_stats(ExponentialBackoff::default(), returns_ok).await {
+///     println!("took {} attempts and {:?} of backoff", stats.attempts, stats.total_sleep);
+/// }
+/// # };
+/// ```
+pub async fn retry_with_stats<B, F, T, E, Fut>(backoff: B, mut func: F) -> Result<(T, RetryStats), (E, RetryStats)>
 where
-    F: Fn() -> Fut,
-    Fut: Future<Output = Result<T, E>>,
-    P: Fn(&E, u32) -> bool,
     B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
 {
-    type Output = Result<T, E>;
+    let start = Instant::now();
+    let mut attempts = 0;
+    let mut total_sleep = Duration::ZERO;
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut this = self.project();
-
-        if let Some(paused_until) = this.paused_until {
-            if Instant::now() < *paused_until {
-                return Poll::Pending;
+    let result = retry_notify(
+        backoff,
+        || {
+            attempts += 1;
+            let first = attempts == 1;
+            let future = func();
+            // The next attempt is created before the backoff period is waited,
+            // so its start is taken once it is first polled.
+            async move {
+                let started = if first { Duration::ZERO } else { start.elapsed() };
+                future.await.map(|value| (value, started)).map_err(|e| (e, started))
             }
+        },
+        |_: &(E, Duration), _, duration: Duration| total_sleep = total_sleep.saturating_add(duration),
+    )
+    .await;
 
-            *this.paused_until = None;
-        }
-
-        let result = match this.future.as_mut().poll(cx) {
-            Poll::Ready(res) => res,
-            Poll::Pending => return Poll::Pending,
-        };
-
-        match result {
-            Ok(value) => return Poll::Ready(Ok(value)),
-            Err(e) => {
-                *this.iterations += 1;
-                let can_continue = (this.predicate)(&e, *this.iterations);
-
-                if !can_continue {
-                    return Poll::Ready(Err(e));
-                }
+    // The iterations of the stats are zero-based, the same as the sync
+    // functions, and the final attempt only counts if it failed.
+    let failures = if result.is_ok() { attempts - 1 } else { attempts };
+    let mut stats = RetryStats {
+        attempts,
+        total_sleep,
+        elapsed: start.elapsed(),
+        last_attempt: Duration::ZERO,
+        last_error_iteration: failures.checked_sub(1),
+    };
 
-                let new_future = (this.factory)();
-                this.future.set(new_future);
+    match result {
+        Ok((value, last_attempt)) => {
+            stats.last_attempt = last_attempt;
+            Ok((value, stats))
+        }
+        Err((e, last_attempt)) => {
+            stats.last_attempt = last_attempt;
+            Err((e, stats))
+        }
+    }
+}
 
-                let duration = this.backoff.backoff_period(*this.iterations);
-                let waker = cx.waker().clone();
+/// The same as [retry_if](crate::future::retry_if) but when retrying stops
+/// every error encountered is returned in the order they occurred, up to
+/// `limit`, the same as [sync::retry_if_collect](crate::sync::retry_if_collect).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), std::io::Error> {
+///     Err(std::io::ErrorKind::ConnectionRefused.into())
+/// }
+///
+/// let result = tryagain::future::retry_if_collect(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations| iterations < 3,
+///     HistoryLimit::Last(10),
+/// )
+/// .await;
+/// # };
+/// ```
+pub async fn retry_if_collect<B, F, P, T, E, Fut>(
+    mut backoff: B,
+    mut func: F,
+    predicate: P,
+    limit: HistoryLimit,
+) -> Result<T, Vec<E>>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut history = History::new(limit);
+    let mut iterations = 0;
 
-                *this.paused_until = Some(Instant::now() + duration);
+    loop {
+        let result = func().await;
+        trace::attempted(None);
 
-                // This is a hack to call the waker, I don't have a better way
-                // to do this other than looping, which would block.
-                #[cfg(feature = "runtime-tokio")]
-                tokio::spawn(async move {
-                    tokio::time::sleep(duration).await;
-                    waker.wake();
-                });
+        let e = match result {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
 
-                #[cfg(feature = "runtime-async-std")]
-                async_std::task::spawn(async move {
-                    async_std::task::sleep(duration).await;
-                    waker.wake();
-                });
+        iterations += 1;
+        let next = if predicate(&e, iterations) {
+            schedule.next(&mut backoff, iterations, start.elapsed())
+        } else {
+            None
+        };
+        history.push(e);
 
-                Poll::Pending
+        match next {
+            Some(duration) => {
+                trace::retrying(None, iterations, duration);
+                timer::sleep(duration).await;
+            }
+            None => {
+                trace::gave_up(None);
+                return Err(history.into_vec());
             }
         }
     }
 }
+
+/// The same as [retry_if](crate::future::retry_if) but when an error has a
+/// [retry_after](crate::RetryDelayHint::retry_after) hint it is waited for
+/// instead of the backoff's delay, the same as
+/// [sync::retry_with_hints](crate::sync::retry_with_hints).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// # async {
+/// struct TooManyRequests {
+///     retry_after: Option<Duration>,
+/// }
+///
+/// impl RetryDelayHint for TooManyRequests {
+///     fn retry_after(&self) -> Option<Duration> {
+///         self.retry_after
+///     }
+/// }
+///
+/// async fn returns_err() -> Result<(), TooManyRequests> {
+///     Err(TooManyRequests {
+///         retry_after: Some(Duration::from_millis(10)),
+///     })
+/// }
+///
+/// let result = tryagain::future::retry_with_hints(ExponentialBackoff::default(), returns_err, |_error, iterations| iterations < 3).await;
+/// # };
+/// ```
+pub async fn retry_with_hints<B, F, P, T, E, Fut>(backoff: B, mut func: F, predicate: P) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+    E: RetryDelayHint,
+{
+    let start = Instant::now();
+    let mut backoff = HintedBackoff::new(backoff, E::retry_after);
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        let result = func().await;
+        trace::attempted(None);
+
+        let e = match result {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        iterations += 1;
+        let next = if predicate(&e, iterations) {
+            schedule.next_with(iterations, start.elapsed(), |ctx| backoff.next_backoff_for(&e, ctx))
+        } else {
+            None
+        };
+
+        match next {
+            Some(duration) => {
+                trace::retrying(None, iterations, duration);
+                timer::sleep(duration).await;
+            }
+            None => {
+                trace::gave_up(None);
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// The same as [retry_if](crate::future::retry_if) but the error that stops
+/// the retrying is returned as a [RetryError](crate::sync::RetryError), which
+/// also carries the number of attempts made and the time spent retrying. The
+/// attempts are counted the same as
+/// [sync::retry_if_detailed](crate::sync::retry_if_detailed).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), &'static str> {
+///     Err("connection refused")
+/// }
+///
+/// let result = tryagain::future::retry_if_detailed(ExponentialBackoff::default(), returns_err, |_error, iterations| {
+///     iterations < 3
+/// })
+/// .await;
+///
+/// if let Err(error) = result {
+///     println!("{} after {} attempts in {:?}", error, error.attempts(), error.elapsed());
+/// }
+/// # };
+/// ```
+pub async fn retry_if_detailed<B, F, P, T, E, Fut>(backoff: B, mut func: F, predicate: P) -> Result<T, RetryError<E>>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    let mut attempts = 0;
+
+    let result = retry_if(
+        backoff,
+        || {
+            attempts += 1;
+            func()
+        },
+        predicate,
+    )
+    .await;
+
+    result.map_err(|error| RetryError::new(error, attempts, start.elapsed()))
+}
+
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows, calling `notify` with the error, the number of iterations and the
+/// backoff period after every failed attempt, before waiting.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let value = tryagain::future::retry_notify(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations, duration| println!("attempt {} failed, retrying in {:?}", iterations, duration),
+/// ).await;
+/// # };
+/// ```
+pub fn retry_notify<B, F, N, T, E, Fut>(
+    backoff: B,
+    func: F,
+    notify: N,
+) -> RetryFuture<F, Fut, impl Fn(&E, u32) -> bool, B, N>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    N: FnMut(&E, u32, Duration),
+{
+    retry_if_notify(backoff, func, |_: &E, _| true, notify)
+}
+
+/// The same as [retry_if](crate::future::retry_if) but `notify` is called with
+/// the error, the number of iterations and the backoff period once the
+/// predicate has decided to retry, before waiting. `notify` isn't called for
+/// the final error that is returned.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), std::io::Error> {
+///     Err(std::io::ErrorKind::ConnectionRefused.into())
+/// }
+///
+/// let result = tryagain::future::retry_if_notify(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations| iterations < 3,
+///     |error, iterations, duration| eprintln!("attempt {} failed: {}, retrying in {:?}", iterations, error, duration),
+/// ).await;
+/// # };
+/// ```
+pub fn retry_if_notify<B, F, P, N, T, E, Fut>(backoff: B, func: F, predicate: P, notify: N) -> RetryFuture<F, Fut, P, B, N>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+    N: FnMut(&E, u32, Duration),
+{
+    RetryFuture::new(backoff, func, predicate, notify)
+}
+
+#[pin_project::pin_project]
+/// A future that will retry an operation.
+///
+/// # Send
+/// The backoff period is waited out inside the future itself rather than on a
+/// spawned task, so a [RetryFuture](crate::future::RetryFuture) is `Send` if
+/// and only if the factory, the futures it returns, the predicate and the
+/// backoff are all `Send` (except with `runtime-wasm` on `wasm32`, where it is
+/// never `Send`). Futures holding `!Send` state can still be retried, they just
+/// have to be awaited on a single thread, such as a current-thread runtime or
+/// a local task set.
+///
+/// ```compile_fail
+/// # use tryagain::*;
+/// # use std::rc::Rc;
+/// fn assert_send<T: Send>(_: T) {}
+///
+/// let state = Rc::new(());
+/// assert_send(tryagain::future::retry(ImmediateBackoff, move || {
+///     let state = state.clone();
+///     async move {
+///         drop(state);
+///         Err::<(), ()>(())
+///     }
+/// }));
+/// ```
+pub struct RetryFuture<F, Fut, P, B, N = NoNotify> {
+    #[pin]
+    state: RetryState<F, Fut, P, B, N>,
+    delay: Delay,
+    fair_yield: bool,
+}
+
+/// The state of the backoff period between two attempts of a
+/// [RetryFuture](crate::future::RetryFuture).
+enum Delay {
+    /// The next attempt can be polled.
+    Ready,
+    /// Waiting for the backoff period to elapse before polling the next
+    /// attempt. Dropping the timer cancels the wait.
+    Sleeping(Timer),
+}
+
+impl fmt::Debug for Delay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Delay::Ready => f.write_str("Ready"),
+            Delay::Sleeping(_) => f.write_str("Sleeping"),
+        }
+    }
+}
+
+impl<F, Fut, P, B: fmt::Debug, N> fmt::Debug for RetryFuture<F, Fut, P, B, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryFuture")
+            .field("state", &self.state)
+            .field("delay", &self.delay)
+            .field("fair_yield", &self.fair_yield)
+            .finish()
+    }
+}
+
+impl<F, Fut, P, B, N> RetryFuture<F, Fut, P, B, N> {
+    fn new(backoff: B, func: F, predicate: P, notify: N) -> Self
+    where
+        F: FnMut() -> Fut,
+        B: Backoff,
+    {
+        Self {
+            state: RetryState::with_notify(backoff, func, predicate, notify),
+            delay: Delay::Ready,
+            fair_yield: true,
+        }
+    }
+
+    /// Sets whether the future yields to the executor before retrying when the
+    /// backoff period is zero, which is the default.
+    ///
+    /// Yielding lets other tasks on the same executor make progress between
+    /// attempts, at the cost of a round trip through the executor for every
+    /// retry. Disabling it retries immediately within the same poll, which is
+    /// faster but can starve other tasks on single threaded executors for as
+    /// long as the attempts keep failing without ever being pending.
+    pub fn fair_yield(mut self, fair_yield: bool) -> Self {
+        self.fair_yield = fair_yield;
+        self
+    }
+
+    /// Sets the name of the operation being retried, used to label the
+    /// metrics emitted with the `metrics` feature.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.state = self.state.with_name(name);
+        self
+    }
+}
+
+impl<T, E, F, Fut, P, B, N> Future for RetryFuture<F, Fut, P, B, N>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+    B: Backoff,
+    N: Notify<E>,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        loop {
+            if let Delay::Sleeping(timer) = this.delay {
+                if timer.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                *this.delay = Delay::Ready;
+            }
+
+            if let Poll::Ready(result) = this.state.as_mut().poll_attempt(cx) {
+                return Poll::Ready(result);
+            }
+
+            let duration = match this.state.next_wait() {
+                Some(duration) => duration,
+                None => return Poll::Pending,
+            };
+
+            if !duration.is_zero() {
+                *this.delay = Delay::Sleeping(timer::sleep(duration));
+            } else if *this.fair_yield {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+/// The same as [retry_if](crate::future::retry_if) but the predicate returns a
+/// future, allowing it to await things like a circuit breaker or a token
+/// refresh before deciding if the function should be retried.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// async fn should_retry(iterations: u32) -> bool {
+///     iterations < 3
+/// }
+///
+/// let result = tryagain::future::retry_if_async(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations| should_retry(iterations),
+/// ).await;
+/// # };
+/// ```
+pub fn retry_if_async<B, F, P, T, E, Fut, PFut>(
+    mut backoff: B,
+    mut func: F,
+    predicate: P,
+) -> AsyncRetryFuture<F, Fut, P, PFut, B, E>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> PFut,
+    PFut: Future<Output = bool>,
+{
+    let future = func();
+    let schedule = Schedule::start(&mut backoff);
+
+    AsyncRetryFuture {
+        factory: func,
+        future,
+        predicate,
+        decision: None,
+        error: None,
+        backoff,
+        delay: Delay::Ready,
+        started: Instant::now(),
+        schedule,
+        iterations: 0,
+    }
+}
+
+#[pin_project::pin_project]
+/// A future that will retry an operation, deciding if it should be retried
+/// with an asynchronous predicate.
+pub struct AsyncRetryFuture<F, Fut, P, PFut, B, E> {
+    factory: F,
+    #[pin]
+    future: Fut,
+    predicate: P,
+    #[pin]
+    decision: Option<PFut>,
+    error: Option<E>,
+    backoff: B,
+    delay: Delay,
+    started: Instant,
+    schedule: Schedule,
+    iterations: u32,
+}
+
+impl<T, E, F, Fut, P, PFut, B> Future for AsyncRetryFuture<F, Fut, P, PFut, B, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> PFut,
+    PFut: Future<Output = bool>,
+    B: Backoff,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        loop {
+            if let Delay::Sleeping(timer) = this.delay {
+                if timer.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                *this.delay = Delay::Ready;
+            }
+
+            if let Some(decision) = this.decision.as_mut().as_pin_mut() {
+                let can_continue = match decision.poll(cx) {
+                    Poll::Ready(can_continue) => can_continue,
+                    Poll::Pending => return Poll::Pending,
+                };
+
+                this.decision.set(None);
+                let e = this.error.take().expect("decision without an error");
+
+                if !can_continue {
+                    trace::gave_up(None);
+                    return Poll::Ready(Err(e));
+                }
+
+                let elapsed = this.started.elapsed();
+                let duration = match this.schedule.next(this.backoff, *this.iterations, elapsed) {
+                    Some(duration) => duration,
+                    None => {
+                        trace::gave_up(None);
+                        return Poll::Ready(Err(e));
+                    }
+                };
+                trace::retrying(None, *this.iterations, duration);
+
+                let new_future = (this.factory)();
+                this.future.set(new_future);
+
+                if duration.is_zero() {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+
+                *this.delay = Delay::Sleeping(timer::sleep(duration));
+                continue;
+            }
+
+            let result = match this.future.as_mut().poll(cx) {
+                Poll::Ready(res) => res,
+                Poll::Pending => return Poll::Pending,
+            };
+            trace::attempted(None);
+
+            match result {
+                Ok(value) => return Poll::Ready(Ok(value)),
+                Err(e) => {
+                    *this.iterations += 1;
+                    this.decision.set(Some((this.predicate)(&e, *this.iterations)));
+                    *this.error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+/// Returns a [Stream](futures_core::Stream) that yields the result of every
+/// attempt, retrying the provided function whenever the backoff allows. The
+/// stream ends after the first successful attempt or once the backoff gives
+/// up.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// // Yields an error for every attempt, forever.
+/// let stream = tryagain::future::retry_stream(ExponentialBackoff::default(), returns_err);
+/// # };
+/// ```
+pub fn retry_stream<B, F, T, E, Fut>(
+    backoff: B,
+    func: F,
+) -> RetryStream<F, Fut, impl Fn(&E, u32) -> bool, B>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_stream_if(backoff, func, |_, _| true)
+}
+
+/// The same as [retry_stream](crate::future::retry_stream) but the stream also
+/// ends after an error the predicate doesn't allow to be retried.
+pub fn retry_stream_if<B, F, P, T, E, Fut>(
+    mut backoff: B,
+    mut func: F,
+    predicate: P,
+) -> RetryStream<F, Fut, P, B>
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+{
+    let future = Some(func());
+    let schedule = Schedule::start(&mut backoff);
+
+    RetryStream {
+        factory: func,
+        future,
+        predicate,
+        backoff,
+        delay: Delay::Ready,
+        started: Instant::now(),
+        schedule,
+        iterations: 0,
+        reset_on_success: false,
+        idle: false,
+    }
+}
+
+impl<F, Fut, P, PFut, B: fmt::Debug, E> fmt::Debug for AsyncRetryFuture<F, Fut, P, PFut, B, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncRetryFuture")
+            .field("iterations", &self.iterations)
+            .field("delay", &self.delay)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+#[pin_project::pin_project]
+/// A stream that yields the result of every attempt of an operation.
+pub struct RetryStream<F, Fut, P, B> {
+    factory: F,
+    #[pin]
+    future: Option<Fut>,
+    predicate: P,
+    backoff: B,
+    delay: Delay,
+    started: Instant,
+    schedule: Schedule,
+    iterations: u32,
+    reset_on_success: bool,
+    /// Set after a success with `reset_on_success`, the next attempt is only
+    /// started once another item is polled for.
+    idle: bool,
+}
+
+impl<F, Fut, P, B> RetryStream<F, Fut, P, B> {
+    /// Keeps running the operation after it succeeds instead of ending the
+    /// stream, for operations that run in a loop for as long as the stream is
+    /// polled.
+    ///
+    /// After every success the backoff is
+    /// [reset](crate::backoff::Backoff::reset) and the number of iterations
+    /// goes back to zero, so a later failure backs off from the start again
+    /// rather than continuing from where the previous failures left off. The
+    /// next attempt is only started once the stream is polled again.
+    ///
+    /// # Example
+    /// ```
+    /// # use tryagain::*;
+    /// # async {
+    /// async fn poll_queue() -> Result<Vec<u8>, ()> {
+    ///     Ok(Vec::new())
+    /// }
+    ///
+    /// // Yields every batch and every error, forever.
+    /// let stream = tryagain::future::retry_stream(ExponentialBackoff::default(), poll_queue).reset_on_success();
+    /// # };
+    /// ```
+    pub fn reset_on_success(mut self) -> Self {
+        self.reset_on_success = true;
+        self
+    }
+}
+
+impl<F, Fut, P, B: fmt::Debug> fmt::Debug for RetryStream<F, Fut, P, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryStream")
+            .field("iterations", &self.iterations)
+            .field("delay", &self.delay)
+            .field("backoff", &self.backoff)
+            .field("reset_on_success", &self.reset_on_success)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, E, F, Fut, P, B> Stream for RetryStream<F, Fut, P, B>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+    B: Backoff,
+{
+    type Item = Result<T, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if let Delay::Sleeping(timer) = this.delay {
+            if timer.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            *this.delay = Delay::Ready;
+        }
+
+        if *this.idle {
+            *this.idle = false;
+            this.future.set(Some((this.factory)()));
+        }
+
+        let future = match this.future.as_mut().as_pin_mut() {
+            Some(future) => future,
+            None => return Poll::Ready(None),
+        };
+
+        let result = match future.poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        trace::attempted(None);
+
+        let e = match result {
+            Ok(value) => {
+                this.future.set(None);
+                if *this.reset_on_success {
+                    *this.schedule = Schedule::start(this.backoff);
+                    *this.started = Instant::now();
+                    *this.iterations = 0;
+                    *this.idle = true;
+                }
+
+                return Poll::Ready(Some(Ok(value)));
+            }
+            Err(e) => e,
+        };
+
+        *this.iterations += 1;
+        if !(this.predicate)(&e, *this.iterations) {
+            trace::gave_up(None);
+            this.future.set(None);
+            return Poll::Ready(Some(Err(e)));
+        }
+
+        let elapsed = this.started.elapsed();
+        let duration = match this.schedule.next(this.backoff, *this.iterations, elapsed) {
+            Some(duration) => duration,
+            None => {
+                trace::gave_up(None);
+                this.future.set(None);
+                return Poll::Ready(Some(Err(e)));
+            }
+        };
+        trace::retrying(None, *this.iterations, duration);
+
+        this.future.set(Some((this.factory)()));
+
+        if !duration.is_zero() {
+            *this.delay = Delay::Sleeping(timer::sleep(duration));
+        }
+
+        Poll::Ready(Some(Err(e)))
+    }
+}
+
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows, returning a [CancelHandle](crate::future::CancelHandle) that can be
+/// used to stop retrying from elsewhere.
+///
+/// Cancellation is checked before every attempt and during the backoff
+/// period, so the future resolves to
+/// [CancellableError::Cancelled](crate::future::CancellableError::Cancelled)
+/// promptly after [cancel](crate::future::CancelHandle::cancel) is called.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let (future, handle) = tryagain::future::retry_cancellable(ExponentialBackoff::default(), returns_err);
+/// handle.cancel();
+///
+/// let result = future.await;
+/// # };
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn retry_cancellable<B, F, T, E, Fut>(
+    backoff: B,
+    func: F,
+) -> (
+    CancellableRetryFuture<F, Fut, impl Fn(&E, u32) -> bool, B>,
+    CancelHandle,
+)
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let handle = CancelHandle::default();
+    let future = CancellableRetryFuture {
+        inner: retry(backoff, func),
+        handle: handle.clone(),
+    };
+
+    (future, handle)
+}
+
+/// A handle used to cancel a
+/// [CancellableRetryFuture](crate::future::CancellableRetryFuture).
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl CancelHandle {
+    /// Cancels the retry, waking the future so it can resolve.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+
+        if let Some(waker) = self.inner.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+
+    /// Returns true if the retry has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn register(&self, waker: &Waker) {
+        *self.inner.waker.lock().unwrap() = Some(waker.clone());
+    }
+}
+
+/// The error returned by a
+/// [CancellableRetryFuture](crate::future::CancellableRetryFuture).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancellableError<E> {
+    /// The retry was cancelled through its
+    /// [CancelHandle](crate::future::CancelHandle).
+    Cancelled,
+    /// The predicate stopped retrying after this error.
+    Error(E),
+}
+
+#[pin_project::pin_project]
+/// A [RetryFuture](crate::future::RetryFuture) that can be cancelled with a
+/// [CancelHandle](crate::future::CancelHandle).
+pub struct CancellableRetryFuture<F, Fut, P, B> {
+    #[pin]
+    inner: RetryFuture<F, Fut, P, B>,
+    handle: CancelHandle,
+}
+
+impl<F, Fut, P, B: fmt::Debug> fmt::Debug for CancellableRetryFuture<F, Fut, P, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellableRetryFuture")
+            .field("inner", &self.inner)
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+impl<T, E, F, Fut, P, B> Future for CancellableRetryFuture<F, Fut, P, B>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+    B: Backoff,
+{
+    type Output = Result<T, CancellableError<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        this.handle.register(cx.waker());
+        if this.handle.is_cancelled() {
+            return Poll::Ready(Err(CancellableError::Cancelled));
+        }
+
+        this.inner.poll(cx).map_err(CancellableError::Error)
+    }
+}
+
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows, returning an [AbortHandle](crate::future::AbortHandle) that can
+/// abort the retry.
+///
+/// Once [abort](crate::future::AbortHandle::abort) is called, or every clone
+/// of the handle has been dropped, the future resolves to
+/// `Err(`[RetryAborted](crate::future::RetryAborted)`)` the next time it is
+/// polled. Aborting wakes the future, so it resolves promptly even while it is
+/// waiting out a backoff period.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # async {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let (future, handle) = tryagain::future::retry_abortable(ExponentialBackoff::default(), returns_err);
+/// drop(handle);
+///
+/// assert_eq!(future.await, Err(tryagain::future::RetryAborted));
+/// # };
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn retry_abortable<B, F, T, E, Fut>(
+    backoff: B,
+    func: F,
+) -> (
+    AbortableRetryFuture<F, Fut, impl Fn(&E, u32) -> bool, B>,
+    AbortHandle,
+)
+where
+    B: Backoff,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let state = Arc::new(AbortState {
+        aborted: AtomicBool::new(false),
+        handles: AtomicUsize::new(1),
+        waker: Mutex::new(None),
+    });
+    let future = AbortableRetryFuture {
+        inner: retry(backoff, func),
+        state: state.clone(),
+    };
+
+    (future, AbortHandle { state })
+}
+
+/// A handle used to abort an
+/// [AbortableRetryFuture](crate::future::AbortableRetryFuture). Dropping every
+/// clone of the handle aborts the retry as well.
+#[derive(Debug)]
+pub struct AbortHandle {
+    state: Arc<AbortState>,
+}
+
+#[derive(Debug)]
+struct AbortState {
+    aborted: AtomicBool,
+    handles: AtomicUsize,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl AbortState {
+    fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
+
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl AbortHandle {
+    /// Aborts the retry, waking the future so it can resolve.
+    pub fn abort(&self) {
+        self.state.abort();
+    }
+
+    /// Returns true if the retry has been aborted.
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.load(Ordering::SeqCst)
+    }
+}
+
+impl Clone for AbortHandle {
+    fn clone(&self) -> Self {
+        self.state.handles.fetch_add(1, Ordering::SeqCst);
+
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Drop for AbortHandle {
+    fn drop(&mut self) {
+        if self.state.handles.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.state.abort();
+        }
+    }
+}
+
+/// The error an [AbortableRetryFuture](crate::future::AbortableRetryFuture)
+/// resolves to once it has been aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryAborted;
+
+impl fmt::Display for RetryAborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the retry was aborted")
+    }
+}
+
+impl std::error::Error for RetryAborted {}
+
+#[pin_project::pin_project]
+/// A [RetryFuture](crate::future::RetryFuture) that can be aborted with an
+/// [AbortHandle](crate::future::AbortHandle).
+pub struct AbortableRetryFuture<F, Fut, P, B> {
+    #[pin]
+    inner: RetryFuture<F, Fut, P, B>,
+    state: Arc<AbortState>,
+}
+
+impl<F, Fut, P, B: fmt::Debug> fmt::Debug for AbortableRetryFuture<F, Fut, P, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AbortableRetryFuture")
+            .field("inner", &self.inner)
+            .field("aborted", &self.state.aborted)
+            .finish()
+    }
+}
+
+impl<T, E, F, Fut, P, B> Future for AbortableRetryFuture<F, Fut, P, B>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+    B: Backoff,
+{
+    type Output = Result<Result<T, E>, RetryAborted>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        *this.state.waker.lock().unwrap() = Some(cx.waker().clone());
+        if this.state.aborted.load(Ordering::SeqCst) {
+            return Poll::Ready(Err(RetryAborted));
+        }
+
+        this.inner.poll(cx).map(Ok)
+    }
+}
+
+#[cfg(all(test, feature = "runtime-tokio"))]
+mod tests {
+    use super::*;
+    use crate::{
+        BackoffExt, ConstantBackoff, ExponentialBackoff, FnBackoff, ImmediateBackoff, IteratorBackoff, MinimumBackoff, OnExhausted,
+    };
+
+    #[tokio::test]
+    async fn test_debug() {
+        let mut future = Box::pin(retry(ImmediateBackoff, || async { Err::<(), ()>(()) }));
+        assert!(format!("{:?}", future).contains("iterations: 0"));
+
+        let poll = std::future::poll_fn(|cx| Poll::Ready(future.as_mut().poll(cx))).await;
+        assert!(poll.is_pending());
+
+        let debug = format!("{:?}", future);
+        assert!(debug.starts_with("RetryFuture"));
+        assert!(debug.contains("iterations: 1"));
+        assert!(debug.contains("ImmediateBackoff"));
+    }
+
+    #[test]
+    fn test_retry_future_is_send() {
+        fn assert_send<T: Send>(_: T) {}
+
+        assert_send(retry(ImmediateBackoff, || async { Err::<(), ()>(()) }));
+    }
+
+    #[tokio::test]
+    async fn test_not_send_future() {
+        let state = std::rc::Rc::new(std::cell::Cell::new(0));
+
+        let result = retry(ImmediateBackoff, || {
+            let state = state.clone();
+            async move {
+                state.set(state.get() + 1);
+                tokio::task::yield_now().await;
+
+                if state.get() < 3 {
+                    Err(())
+                } else {
+                    Ok(state.get())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn test_retry_n() {
+        for (n, expected) in [(0, 1), (1, 1), (2, 2), (5, 5)] {
+            let mut attempts = 0;
+            let result = retry_n(ImmediateBackoff, n, || {
+                attempts += 1;
+                let attempt = attempts;
+                async move { Err::<(), _>(attempt) }
+            })
+            .await;
+
+            assert_eq!(result, Err(expected));
+        }
+
+        let mut attempts = 0;
+        let result = retry_n(ImmediateBackoff, 3, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn test_retry_if_detailed() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(5));
+        let result = retry_if_detailed(backoff, || async { Err::<(), _>("refused") }, |_, iterations| iterations < 3).await;
+        let error = result.unwrap_err();
+
+        // The same attempts as the sync version with an equivalent predicate.
+        assert_eq!(error.attempts(), 3);
+        assert!(error.elapsed() >= Duration::from_millis(10));
+        assert_eq!(error.into_inner(), "refused");
+    }
+
+    #[tokio::test]
+    async fn test_retry_if_collect() {
+        let collect = |limit| {
+            let mut attempts = 0;
+            retry_if_collect(
+                ImmediateBackoff,
+                move || {
+                    attempts += 1;
+                    let attempt = attempts;
+                    async move { Err::<(), _>(attempt) }
+                },
+                |_, iterations| iterations < 5,
+                limit,
+            )
+        };
+
+        assert_eq!(collect(HistoryLimit::All).await, Err(vec![1, 2, 3, 4, 5]));
+        assert_eq!(collect(HistoryLimit::First(2)).await, Err(vec![1, 2]));
+        assert_eq!(collect(HistoryLimit::Last(2)).await, Err(vec![4, 5]));
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_stats() {
+        let (value, stats) = retry_with_stats(ImmediateBackoff, || async { Ok::<_, ()>(1) }).await.unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(stats.attempts, 1);
+        assert_eq!(stats.total_sleep, Duration::ZERO);
+        assert_eq!(stats.last_error_iteration, None);
+
+        let period = Duration::from_millis(5);
+        let mut attempts = 0;
+        let result = retry_with_stats(MinimumBackoff::new(ImmediateBackoff, period), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 4 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        let (value, stats) = result.unwrap();
+        assert_eq!(value, 4);
+        assert_eq!(stats.attempts, 4);
+        assert_eq!(stats.total_sleep, period * 3);
+        assert_eq!(stats.last_error_iteration, Some(2));
+        assert!(stats.last_attempt >= period * 3);
+    }
+
+    #[tokio::test]
+    async fn test_fn_mut_factory() {
+        let mut attempts = 0;
+        let result = retry_if(
+            ImmediateBackoff,
+            || {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt < 3 {
+                        Err(attempt)
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            |_, _| true,
+        )
+        .await;
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(attempts, 3);
+    }
+
+    async fn retry_while_competing(fair_yield: bool) -> bool {
+        let progressed = Arc::new(AtomicBool::new(false));
+        let competitor = progressed.clone();
+        tokio::spawn(async move { competitor.store(true, Ordering::SeqCst) });
+
+        let mut attempts = 0;
+        let result = retry(ImmediateBackoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 100 {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .fair_yield(fair_yield)
+        .await;
+
+        assert_eq!(result, Ok(()));
+        progressed.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_fair_yield_lets_others_progress() {
+        assert!(retry_while_competing(true).await);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_no_fair_yield_starves_others() {
+        assert!(!retry_while_competing(false).await);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_semaphore() {
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(2));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                let mut attempts = 0;
+
+                tokio::spawn(retry_with_semaphore(
+                    ImmediateBackoff,
+                    move || {
+                        attempts += 1;
+                        let attempt = attempts;
+                        let in_flight = in_flight.clone();
+                        let max_in_flight = max_in_flight.clone();
+
+                        async move {
+                            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            max_in_flight.fetch_max(current, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(5)).await;
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                            if attempt < 3 {
+                                Err(())
+                            } else {
+                                Ok(attempt)
+                            }
+                        }
+                    },
+                    semaphore.clone(),
+                ))
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), Ok(3));
+        }
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_option() {
+        let mut attempts = 0;
+
+        let value = retry_option(ImmediateBackoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    None
+                } else {
+                    Some(42)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(value, Some(42));
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_enumerated() {
+        let attempts = Mutex::new(Vec::new());
+
+        let result = retry_enumerated(ImmediateBackoff, |attempt| {
+            attempts.lock().unwrap().push(attempt);
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(*attempts.lock().unwrap(), [0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_for() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(70));
+        let start = Instant::now();
+
+        let result = retry_for(backoff, || async { Err::<(), _>(()) }, Duration::from_millis(300)).await;
+
+        assert_eq!(result, Err(()));
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(start.elapsed() < Duration::from_millis(350));
+    }
+
+    #[tokio::test]
+    async fn test_notify() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(5));
+        let mut attempts = 0;
+
+        let notified = calls.clone();
+        let result = retry_notify(
+            backoff,
+            || {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt < 3 {
+                        Err(attempt)
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            move |error: &u32, iterations, duration| {
+                notified.lock().unwrap().push((*error, iterations, duration));
+            },
+        )
+        .await;
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [(1, 1, Duration::from_millis(5)), (2, 2, Duration::from_millis(5))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_if_notify() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut attempts = 0;
+
+        let predicate_events = events.clone();
+        let notify_events = events.clone();
+        let result = retry_if_notify(
+            ImmediateBackoff,
+            || {
+                attempts += 1;
+                let attempt = attempts;
+                async move { Err::<(), _>(attempt) }
+            },
+            move |error, iterations| {
+                predicate_events.lock().unwrap().push(format!("predicate {} {}", error, iterations));
+                iterations < 3
+            },
+            move |error, iterations, _| notify_events.lock().unwrap().push(format!("notify {} {}", error, iterations)),
+        )
+        .await;
+
+        // The final error is returned without being passed to notify.
+        assert_eq!(result, Err(3));
+        assert_eq!(
+            *events.lock().unwrap(),
+            ["predicate 1 1", "notify 1 1", "predicate 2 2", "notify 2 2", "predicate 3 3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_retryable() {
+        #[derive(Debug, PartialEq)]
+        enum Error {
+            Unavailable,
+            InvalidInput,
+        }
+
+        impl Retryable for Error {
+            fn is_retryable(&self) -> bool {
+                matches!(self, Error::Unavailable)
+            }
+        }
+
+        let mut attempts = 0;
+        let result = retry_retryable(ImmediateBackoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(Error::Unavailable)
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result, Ok(3));
+
+        let mut attempts = 0;
+        let result = retry_retryable(ImmediateBackoff, || {
+            attempts += 1;
+            async { Err::<(), _>(Error::InvalidInput) }
+        })
+        .await;
+        assert_eq!(result, Err(Error::InvalidInput));
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_hints() {
+        #[derive(Debug, PartialEq)]
+        enum Error {
+            TooManyRequests(Duration),
+            Unavailable,
+        }
+
+        impl RetryDelayHint for Error {
+            fn retry_after(&self) -> Option<Duration> {
+                match self {
+                    Error::TooManyRequests(retry_after) => Some(*retry_after),
+                    Error::Unavailable => None,
+                }
+            }
+        }
+
+        let hint = Duration::from_millis(60);
+        let fallback = Duration::from_millis(5);
+        let mut attempts = 0;
+        let start = Instant::now();
+
+        let result = retry_with_hints(
+            MinimumBackoff::new(ImmediateBackoff, fallback),
+            || {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    match attempt {
+                        1 => Err(Error::TooManyRequests(hint)),
+                        2 => Err(Error::Unavailable),
+                        attempt => Ok(attempt),
+                    }
+                }
+            },
+            |_, _| true,
+        )
+        .await;
+
+        assert_eq!(result, Ok(3));
+        assert!(start.elapsed() >= hint + fallback);
+
+        // The hint wins even when it is shorter than the minimum.
+        let start = Instant::now();
+        let result = retry_with_hints(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10)),
+            || async { Err::<(), _>(Error::TooManyRequests(Duration::from_millis(1))) },
+            |_, iterations| iterations < 3,
+        )
+        .await;
+
+        assert_eq!(result, Err(Error::TooManyRequests(Duration::from_millis(1))));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_retry_constant() {
+        let mut attempts = 0;
+        let value = retry(ConstantBackoff::from_millis(1), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(value, Ok(3));
+
+        let mut attempts = 0;
+        let start = Instant::now();
+        let value = retry(Duration::from_millis(5), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(value, Ok(3));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[tokio::test]
+    async fn test_retry_jitter() {
+        let mut attempts = 0;
+        let start = Instant::now();
+        let result = retry_if(
+            crate::JitterBackoff::equal(ConstantBackoff::from_millis(10)),
+            || {
+                attempts += 1;
+                let attempt = attempts;
+                async move { Err::<(), _>(attempt) }
+            },
+            |_, iterations| iterations < 3,
+        )
+        .await;
+
+        assert_eq!(result, Err(3));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    async fn test_retry_fn_backoff() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let backoff = FnBackoff({
+            let calls = calls.clone();
+            move |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Duration::ZERO
+            }
+        });
+
+        let mut attempts = 0;
+        let value = retry(backoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(value, Ok(3));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_iterator_exhausted() {
+        let schedule = [Duration::from_millis(1); 2];
+        let mut attempts = 0;
+        let result = retry(IteratorBackoff::new(schedule).on_exhausted(OnExhausted::GiveUp), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move { Err::<(), _>(attempt) }
+        })
+        .await;
+
+        assert_eq!(result, Err(3));
+    }
+
+    #[tokio::test]
+    async fn test_retry_combinators() {
+        let start = Instant::now();
+        let mut attempts = 0;
+        let backoff = ImmediateBackoff.plus(Duration::from_millis(5)).map(|delay, _| delay * 2);
+        let value = retry(backoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(value, Ok(3));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_backoff_gives_up() {
+        let backoff = ExponentialBackoff::with_base(2.0).with_max(Duration::ZERO).with_max_elapsed(Duration::from_millis(20));
+        let mut attempts = 0;
+        let result = retry(backoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move { Err::<(), _>(attempt) }
+        })
+        .await;
+
+        assert_eq!(result, Err(attempts));
+    }
+
+    #[tokio::test]
+    async fn test_async_predicate() {
+        let mut attempts = 0;
+        let result = retry_if_async(
+            ImmediateBackoff,
+            || {
+                attempts += 1;
+                async { Err::<(), _>(()) }
+            },
+            |_, iterations| async move {
+                tokio::task::yield_now().await;
+                iterations < 3
+            },
+        )
+        .await;
+
+        assert_eq!(result, Err(()));
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_async_predicate_success() {
+        let mut attempts = 0;
+        let result = retry_if_async(
+            ImmediateBackoff,
+            || {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt < 3 {
+                        Err(())
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            |_, _| async {
+                tokio::task::yield_now().await;
+                true
+            },
+        )
+        .await;
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn test_stream_yields_every_attempt() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(10));
+        let mut attempts = 0;
+        let mut stream = Box::pin(retry_stream(backoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(attempt)
+                } else {
+                    Ok(attempt)
+                }
+            }
+        }));
+
+        let mut results = Vec::new();
+        while let Some(result) = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            results.push(result);
+        }
+
+        assert_eq!(results, [Err(1), Err(2), Ok(3)]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_reset_on_success() {
+        struct Stateful {
+            calls: u32,
+            seen: Arc<Mutex<Vec<(u32, u32)>>>,
+        }
+
+        impl Backoff for Stateful {
+            fn backoff_period(&mut self, iterations: u32) -> Duration {
+                self.calls += 1;
+                self.seen.lock().unwrap().push((iterations, self.calls));
+                Duration::ZERO
+            }
+
+            fn reset(&mut self) {
+                self.calls = 0;
+            }
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let backoff = Stateful {
+            calls: 0,
+            seen: seen.clone(),
+        };
+        let mut outcomes = vec![Err(1), Err(2), Ok(3), Err(4), Ok(5)].into_iter();
+        let mut stream = Box::pin(retry_stream(backoff, || std::future::ready(outcomes.next().unwrap())).reset_on_success());
+
+        let mut results = Vec::new();
+        for _ in 0..5 {
+            let result = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await;
+            results.push(result.unwrap());
+        }
+
+        // No attempt is started after the final success until it's asked for.
+        drop(stream);
+        assert_eq!(results, [Err(1), Err(2), Ok(3), Err(4), Ok(5)]);
+        // The failure after the success starts from the first iteration with
+        // a freshly reset backoff.
+        assert_eq!(*seen.lock().unwrap(), [(1, 1), (2, 2), (1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_while_sleeping() {
+        struct DropCounter(Arc<AtomicUsize>);
+
+        impl Drop for DropCounter {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let created = Arc::new(AtomicUsize::new(0));
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(50));
+
+        let factory_created = created.clone();
+        let factory_dropped = dropped.clone();
+        let future = retry(backoff, move || {
+            factory_created.fetch_add(1, Ordering::SeqCst);
+            let guard = DropCounter(factory_dropped.clone());
+            async move {
+                let _guard = guard;
+                Err::<(), _>(())
+            }
+        });
+
+        let mut future = Box::pin(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+
+        let attempts = created.load(Ordering::SeqCst);
+        drop(future);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(created.load(Ordering::SeqCst), attempts);
+        assert_eq!(dropped.load(Ordering::SeqCst), attempts);
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_value() {
+        let mut counter = 0;
+        let value = retry_until_value(
+            ImmediateBackoff,
+            || {
+                counter += 1;
+                let value = counter;
+                async move { Ok::<_, ()>(value) }
+            },
+            |value| *value >= 5,
+        )
+        .await;
+
+        assert_eq!(value, Ok(5));
+
+        let value = retry_until_value(ImmediateBackoff, || async { Err::<u32, _>("failed") }, |_| false).await;
+        assert_eq!(value, Err("failed"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_value_backoff_gives_up() {
+        let backoff = IteratorBackoff::new(vec![Duration::ZERO; 2]).on_exhausted(OnExhausted::GiveUp);
+        let mut polls = 0;
+        let value = retry_until_value(
+            backoff,
+            || {
+                polls += 1;
+                let progress = polls * 10;
+                async move { Ok::<_, ()>(progress) }
+            },
+            |progress| *progress == 100,
+        )
+        .await;
+
+        // The last value is resolved to even though it isn't done.
+        assert_eq!(value, Ok(30));
+        assert_eq!(polls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_async_backoff() {
+        struct YieldingBackoff {
+            calls: Arc<Mutex<Vec<u32>>>,
+        }
+
+        impl AsyncBackoff for YieldingBackoff {
+            async fn next_delay(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+                tokio::task::yield_now().await;
+                self.calls.lock().unwrap().push(ctx.iterations);
+                Some(Duration::from_millis(1))
+            }
+        }
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let backoff = YieldingBackoff {
+            calls: calls.clone(),
+        };
+        let mut attempts = 0;
+        let value = retry_async_backoff(backoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(value, Ok(3));
+        assert_eq!(*calls.lock().unwrap(), [1, 2]);
+
+        let value = retry_async_backoff(ImmediateBackoff, || async { Ok::<_, ()>(1) }).await;
+        assert_eq!(value, Ok(1));
+    }
+
+    #[tokio::test]
+    async fn test_retry_async_backoff_gives_up() {
+        struct Limited {
+            remaining: u32,
+        }
+
+        impl AsyncBackoff for Limited {
+            async fn next_delay(&mut self, _ctx: &BackoffContext) -> Option<Duration> {
+                self.remaining = self.remaining.checked_sub(1)?;
+                Some(Duration::ZERO)
+            }
+
+            fn restart(&mut self) {
+                self.remaining = 2;
+            }
+        }
+
+        let mut attempts = 0;
+        let result = retry_async_backoff(Limited { remaining: 0 }, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move { Err::<(), _>(attempt) }
+        })
+        .await;
+        assert_eq!(result, Err(3));
+
+        // A blanket implemented backoff gives up the same as with retry.
+        let backoff = ExponentialBackoff::with_base(2.0)
+            .with_max(Duration::ZERO)
+            .with_max_elapsed(Duration::from_millis(20));
+        let result = tokio::time::timeout(
+            Duration::from_millis(500),
+            retry_async_backoff(backoff, || async { Err::<(), _>(()) }),
+        )
+        .await;
+        assert_eq!(result, Ok(Err(())));
+    }
+
+    #[tokio::test]
+    async fn test_abort_during_backoff() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10));
+        let (future, handle) = retry_abortable(backoff, || async { Err::<(), ()>(()) });
+        let future = tokio::spawn(future);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let start = Instant::now();
+        handle.abort();
+
+        assert_eq!(future.await.unwrap(), Err(RetryAborted));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_abort_on_dropped_handles() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10));
+        let (future, handle) = retry_abortable(backoff, || async { Err::<(), ()>(()) });
+        let future = tokio::spawn(future);
+
+        let clone = handle.clone();
+        drop(handle);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!clone.is_aborted());
+
+        let start = Instant::now();
+        drop(clone);
+
+        assert_eq!(future.await.unwrap(), Err(RetryAborted));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_during_backoff() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10));
+        let (future, handle) = retry_cancellable(backoff, || async { Err::<(), _>(()) });
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            handle.cancel();
+        });
+
+        let start = Instant::now();
+        assert_eq!(future.await, Err(CancellableError::Cancelled));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
+
+// The smol timer is only used when neither tokio nor async-std is enabled.
+#[cfg(all(
+    test,
+    feature = "runtime-smol",
+    not(any(feature = "runtime-tokio", feature = "runtime-async-std"))
+))]
+mod smol_tests {
+    use super::*;
+    use crate::{ImmediateBackoff, MinimumBackoff};
+    use std::time::Duration;
+
+    #[test]
+    fn test_flaky() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(10));
+        let mut attempts = 0;
+
+        let result = async_io::block_on(retry(backoff, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        }));
+
+        assert_eq!(result, Ok(3));
+    }
+}
+
+#[cfg(all(test, feature = "runtime-wasm", target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::{ImmediateBackoff, MinimumBackoff};
+    use std::{cell::Cell, time::Duration};
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_fails_once() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(50));
+        let attempts = Cell::new(0);
+        let start = Instant::now();
+
+        let result = retry(backoff, || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                if attempt < 2 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(2));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,44 @@
+//! Helpers for randomizing backoff periods so that many clients failing at the
+//! same time don't retry in lockstep.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+/// Jitters `duration` by an amount derived from hashing `value`, resulting in
+/// a duration between half of and the full `duration`. The same value always
+/// produces the same jitter.
+pub(crate) fn hashed<H: Hash>(duration: Duration, value: &H) -> Duration {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+
+    let fraction = hasher.finish() as f64 / u64::MAX as f64;
+    let half = duration / 2;
+
+    half + half.mul_f64(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_is_reproducible() {
+        let duration = Duration::from_secs(10);
+
+        assert_eq!(hashed(duration, &"timeout"), hashed(duration, &"timeout"));
+        assert_ne!(hashed(duration, &"timeout"), hashed(duration, &"refused"));
+    }
+
+    #[test]
+    fn test_hashed_is_bounded() {
+        let duration = Duration::from_secs(10);
+
+        for value in 0..100 {
+            let jittered = hashed(duration, &value);
+            assert!(jittered >= duration / 2 && jittered <= duration);
+        }
+    }
+}
@@ -26,14 +26,73 @@
 //! let value = tryagain::future::retry(ImmediateBackoff, fails).await;
 //! # };
 //! ```
+//! ## Timing
+//! All delays and deadlines are measured with the monotonic
+//! [Instant](std::time::Instant), so adjustments to the system clock never
+//! affect a retry. The wall clock is only read by
+//! [parse_retry_after](crate::parse_retry_after) to convert an HTTP-date into a
+//! relative duration.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 
-#[cfg(any(feature = "runtime-tokio", feature = "runtime-async-std"))]
+extern crate alloc;
+
+#[cfg(any(
+    feature = "runtime-tokio",
+    feature = "runtime-async-std",
+    feature = "runtime-smol",
+    all(feature = "runtime-wasm", target_arch = "wasm32")
+))]
 pub mod future;
+#[cfg(any(
+    feature = "runtime-tokio",
+    feature = "runtime-async-std",
+    feature = "runtime-smol",
+    all(feature = "runtime-wasm", target_arch = "wasm32")
+))]
+mod timer;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 mod backoff;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "backoff-compat")]
+mod compat;
+#[cfg(feature = "serde")]
+mod config;
+#[cfg(feature = "jitter")]
+mod jitter;
+#[cfg(feature = "std")]
+mod retry_after;
+mod retryable;
+mod rng;
+mod sleeper;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+mod state;
+#[cfg(feature = "std")]
 mod sync;
+mod trace;
 
 pub use backoff::*;
+#[cfg(feature = "std")]
+pub use clock::*;
+#[cfg(feature = "backoff-compat")]
+pub use compat::*;
+#[cfg(feature = "serde")]
+pub use config::*;
+#[cfg(feature = "std")]
+pub use retry_after::*;
+pub use retryable::*;
+pub use rng::*;
+pub use sleeper::*;
+#[cfg(feature = "std")]
+pub use shared::*;
+#[cfg(feature = "std")]
+pub use state::*;
+#[cfg(feature = "std")]
 pub use sync::*;
@@ -0,0 +1,135 @@
+//! Parsing of HTTP `Retry-After` values.
+//!
+//! All retry timing in this crate is measured with the monotonic
+//! [Instant](std::time::Instant) so it can't be affected by adjustments to the
+//! system clock. An HTTP-date `Retry-After` value is the only place a wall
+//! clock is consulted, it is read once by `wall_clock_now` and immediately
+//! converted into a relative [Duration](std::time::Duration).
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Parses a `Retry-After` value, either a number of seconds or an HTTP-date,
+/// into the duration that should be waited from now.
+///
+/// Dates in the past result in a zero duration.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+/// assert_eq!(parse_retry_after("soon"), None);
+/// ```
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    parse_retry_after_at(value, wall_clock_now())
+}
+
+/// The same as [parse_retry_after](crate::retry_after::parse_retry_after) but
+/// HTTP-dates are measured relative to `now` instead of the system clock.
+pub fn parse_retry_after_at(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = parse_http_date(value)?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Reads the wall clock. This must only be used to convert absolute dates into
+/// relative durations, never to measure elapsed time.
+fn wall_clock_now() -> SystemTime {
+    SystemTime::now()
+}
+
+/// Parses an IMF-fixdate such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let mut parts = value.split_whitespace();
+    let _weekday = parts.next()?.strip_suffix(',')?;
+    let day: u64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u64 + 1;
+    let year: u64 = parts.next()?.parse().ok()?;
+
+    let mut time = parts.next()?.split(':');
+    let hour: u64 = time.next()?.parse().ok()?;
+    let minute: u64 = time.next()?.parse().ok()?;
+    let second: u64 = time.next()?.parse().ok()?;
+
+    if parts.next()? != "GMT" || parts.next().is_some() || time.next().is_some() {
+        return None;
+    }
+
+    if year < 1970 || day == 0 || day > 31 || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since the unix epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era.checked_mul(146097)?.checked_add(doe)?.checked_sub(719468)?;
+
+    // Absurdly large years overflow rather than wrap around.
+    let seconds = days
+        .checked_mul(86400)?
+        .checked_add(hour * 3600 + minute * 60 + second)?;
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seconds() {
+        assert_eq!(parse_retry_after(" 30 "), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_http_date() {
+        let date = UNIX_EPOCH + Duration::from_secs(784111777);
+        let now = date - Duration::from_secs(10);
+
+        assert_eq!(
+            parse_retry_after_at("Sun, 06 Nov 1994 08:49:37 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_http_date_in_past() {
+        let now = UNIX_EPOCH + Duration::from_secs(784111777 + 60);
+
+        assert_eq!(
+            parse_retry_after_at("Sun, 06 Nov 1994 08:49:37 GMT", now),
+            Some(Duration::from_secs(0))
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:49:37 PST"), None);
+        assert_eq!(parse_retry_after("tomorrow"), None);
+    }
+
+    #[test]
+    fn test_http_date_overflow() {
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 99999999999999999 08:49:37 GMT"),
+            None
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 18446744073709551615 08:49:37 GMT"),
+            None
+        );
+    }
+}
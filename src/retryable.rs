@@ -0,0 +1,134 @@
+use core::time::Duration;
+
+/// Errors that know whether the operation that caused them is worth retrying,
+/// used by [retry_retryable](crate::sync::retry_retryable) and
+/// [future::retry_retryable](crate::future::retry_retryable) instead of a
+/// predicate.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// enum Error {
+///     Unavailable,
+///     InvalidInput,
+/// }
+///
+/// impl Retryable for Error {
+///     fn is_retryable(&self) -> bool {
+///         matches!(self, Error::Unavailable)
+///     }
+/// }
+/// ```
+pub trait Retryable {
+    /// Returns `true` if the operation that returned this error should be
+    /// retried.
+    fn is_retryable(&self) -> bool;
+}
+
+/// Errors that know how long to wait before the operation that caused them is
+/// retried, such as a rate limited request carrying an HTTP `Retry-After`
+/// header, used by [retry_with_hints](crate::sync::retry_with_hints) and
+/// [future::retry_with_hints](crate::future::retry_with_hints).
+///
+/// When a hint is given it replaces the delay of the backoff rather than being
+/// combined with it, so it also takes precedence over the minimum of a
+/// [MinimumBackoff](crate::backoff::MinimumBackoff). The backoff is still
+/// asked for its delay and can give up even when there is a hint.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// enum Error {
+///     TooManyRequests { retry_after: Duration },
+///     Unavailable,
+/// }
+///
+/// impl RetryDelayHint for Error {
+///     fn retry_after(&self) -> Option<Duration> {
+///         match self {
+///             Error::TooManyRequests { retry_after } => Some(*retry_after),
+///             Error::Unavailable => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait RetryDelayHint {
+    /// Returns how long to wait before retrying, or `None` to use the delay of
+    /// the backoff.
+    fn retry_after(&self) -> Option<Duration>;
+}
+
+/// Retries errors of the kinds [WouldBlock](std::io::ErrorKind::WouldBlock),
+/// [Interrupted](std::io::ErrorKind::Interrupted),
+/// [TimedOut](std::io::ErrorKind::TimedOut),
+/// [ConnectionReset](std::io::ErrorKind::ConnectionReset) and
+/// [ConnectionAborted](std::io::ErrorKind::ConnectionAborted).
+#[cfg(feature = "std")]
+impl Retryable for std::io::Error {
+    fn is_retryable(&self) -> bool {
+        use std::io::ErrorKind;
+
+        matches!(
+            self.kind(),
+            ErrorKind::WouldBlock
+                | ErrorKind::Interrupted
+                | ErrorKind::TimedOut
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+        )
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{retry_retryable, ImmediateBackoff};
+    use std::cell::Cell;
+
+    #[derive(Debug, PartialEq)]
+    enum Error {
+        Throttled,
+        Unavailable,
+        InvalidInput,
+    }
+
+    impl Retryable for Error {
+        fn is_retryable(&self) -> bool {
+            matches!(self, Error::Throttled | Error::Unavailable)
+        }
+    }
+
+    #[test]
+    fn test_transient_errors_retry() {
+        let attempts = Cell::new(0);
+        let result = retry_retryable(ImmediateBackoff, || {
+            attempts.set(attempts.get() + 1);
+
+            match attempts.get() {
+                1 => Err(Error::Throttled),
+                2 => Err(Error::Unavailable),
+                attempt => Ok(attempt),
+            }
+        });
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn test_fatal_errors_short_circuit() {
+        let attempts = Cell::new(0);
+        let result = retry_retryable(ImmediateBackoff, || {
+            attempts.set(attempts.get() + 1);
+
+            if attempts.get() < 2 {
+                Err(Error::Unavailable)
+            } else {
+                Err::<(), _>(Error::InvalidInput)
+            }
+        });
+
+        assert_eq!(result, Err(Error::InvalidInput));
+        assert_eq!(attempts.get(), 2);
+    }
+}
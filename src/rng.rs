@@ -0,0 +1,92 @@
+/// A source of random numbers used by
+/// [JitterBackoff](crate::backoff::JitterBackoff) and, with the `jitter`
+/// feature, the `with_rng` constructors of the other randomized backoffs, so
+/// that any random number generator, seeded or not, can be used without
+/// `tryagain` depending on one.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// struct XorShift(u64);
+///
+/// impl Rng for XorShift {
+///     fn next_f64(&mut self) -> f64 {
+///         self.0 ^= self.0 << 13;
+///         self.0 ^= self.0 >> 7;
+///         self.0 ^= self.0 << 17;
+///         (self.0 >> 11) as f64 / (1u64 << 53) as f64
+///     }
+/// }
+///
+/// // Each delay varies within 10% of the exponential backoff's.
+/// let backoff = JitterBackoff::new(ExponentialBackoff::default(), XorShift(7), 0.1);
+/// ```
+pub trait Rng {
+    /// Returns a number in the range `[0, 1)`.
+    fn next_f64(&mut self) -> f64;
+}
+
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn next_f64(&mut self) -> f64 {
+        (**self).next_f64()
+    }
+}
+
+#[cfg(feature = "jitter")]
+impl Rng for fastrand::Rng {
+    fn next_f64(&mut self) -> f64 {
+        self.f64()
+    }
+}
+
+/// A minimal linear congruential generator implementing
+/// [Rng](crate::rng::Rng). It is fast and has no dependencies but its output
+/// is predictable, so it is only suitable for jitter.
+#[cfg(feature = "lcg")]
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    state: u64,
+}
+
+#[cfg(feature = "lcg")]
+impl Lcg {
+    /// Creates an [Lcg](crate::rng::Lcg) starting from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+        }
+    }
+}
+
+#[cfg(feature = "lcg")]
+impl Rng for Lcg {
+    fn next_f64(&mut self) -> f64 {
+        // The constants used by Knuth's MMIX.
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+
+        // The high bits of an LCG are the most random, 53 of them fill the
+        // mantissa of an f64.
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(all(test, feature = "lcg"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lcg() {
+        let mut first = Lcg::new(7);
+        let mut second = Lcg::new(7);
+
+        for _ in 0..1000 {
+            let value = first.next_f64();
+            assert!((0.0..1.0).contains(&value));
+            assert_eq!(value, second.next_f64());
+        }
+    }
+}
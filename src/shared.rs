@@ -0,0 +1,185 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(not(all(feature = "runtime-wasm", target_arch = "wasm32")))]
+use std::time::Instant;
+#[cfg(all(feature = "runtime-wasm", target_arch = "wasm32"))]
+use web_time::Instant;
+
+use crate::{Backoff, BackoffContext};
+
+/// A [Backoff](crate::backoff::Backoff) implementation that spaces out the
+/// retries of every clone by at least `spacing`, limiting the aggregate retry
+/// rate of many concurrent operations against the same service.
+///
+/// Every clone keeps its own copy of the inner backoff, while the next
+/// allowed retry slot is shared between them. Each delay is the longest of
+/// the inner backoff's delay and the time until the next free slot, which is
+/// then reserved.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::{thread, time::Duration};
+/// fn fetch() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// // Retries from all threads are at least 10ms apart.
+/// let backoff = SharedBackoff::new(ExponentialBackoff::default(), Duration::from_millis(10));
+///
+/// let threads: Vec<_> = (0..4)
+///     .map(|_| {
+///         let backoff = backoff.clone();
+///         thread::spawn(move || tryagain::retry_if(backoff, fetch, |_, iterations| iterations < 3))
+///     })
+///     .collect();
+/// # for thread in threads {
+/// #     thread.join().unwrap().expect_err("expected to give up");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedBackoff<T: Backoff> {
+    inner: T,
+    spacing: Duration,
+    next_slot: Arc<Mutex<Option<Instant>>>,
+}
+
+impl<T: Backoff> SharedBackoff<T> {
+    /// Creates a new backoff whose retries, across all of its clones, are at
+    /// least `spacing` apart.
+    pub fn new(inner: T, spacing: Duration) -> Self {
+        Self {
+            inner,
+            spacing,
+            next_slot: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Reserves the first free slot at least `duration` from now, returning
+    /// how long to wait for it. Delays too long to be represented as an
+    /// [Instant] don't reserve anything and are returned as is.
+    fn reserve(&self, duration: Duration) -> Duration {
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        let wanted = match now.checked_add(duration) {
+            Some(wanted) => wanted,
+            None => return duration,
+        };
+        let slot = match *next_slot {
+            Some(next_slot) if next_slot > wanted => next_slot,
+            _ => wanted,
+        };
+        *next_slot = Some(slot.checked_add(self.spacing).unwrap_or(slot));
+
+        slot - now
+    }
+}
+
+impl<T: Backoff> Backoff for SharedBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self.inner.backoff_period(iterations);
+        self.reserve(duration)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        let duration = self.inner.compute(ctx);
+        self.reserve(duration)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(self.reserve(duration))
+    }
+
+    /// Resets the inner backoff, the reserved slots are kept as other clones
+    /// may still be waiting for them.
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{retry_if, ImmediateBackoff, MinimumBackoff};
+    use std::{cell::Cell, thread};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<SharedBackoff<ImmediateBackoff>>();
+    }
+
+    #[test]
+    fn test_reserves_slots() {
+        let spacing = Duration::from_secs(10);
+        let mut first = SharedBackoff::new(ImmediateBackoff, spacing);
+        let mut second = first.clone();
+
+        assert_eq!(first.backoff_period(0), Duration::ZERO);
+        assert!(second.backoff_period(0) > spacing - Duration::from_secs(1));
+        assert!(first.backoff_period(0) > spacing * 2 - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_saturated_durations() {
+        let mut backoff = SharedBackoff::new(
+            MinimumBackoff::new(ImmediateBackoff, Duration::MAX),
+            Duration::from_secs(1),
+        );
+        assert_eq!(backoff.backoff_period(0), Duration::MAX);
+
+        let mut backoff = SharedBackoff::new(ImmediateBackoff, Duration::MAX);
+        assert_eq!(backoff.backoff_period(0), Duration::ZERO);
+        backoff.backoff_period(0);
+    }
+
+    #[test]
+    fn test_spaces_concurrent_retries() {
+        let spacing = Duration::from_millis(20);
+        let backoff = SharedBackoff::new(ImmediateBackoff, spacing);
+        let retries = Arc::new(Mutex::new(Vec::new()));
+        let start = Instant::now();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let backoff = backoff.clone();
+                let retries = retries.clone();
+
+                thread::spawn(move || {
+                    let attempts = Cell::new(0);
+                    let _ = retry_if(
+                        backoff,
+                        || {
+                            if attempts.get() > 0 {
+                                retries.lock().unwrap().push(Instant::now());
+                            }
+                            attempts.set(attempts.get() + 1);
+                            Err::<(), _>(())
+                        },
+                        |_, iterations| iterations < 3,
+                    );
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Every retry happens no earlier than its reserved slot, so the n-th
+        // retry can't happen before n slots have passed.
+        let mut retries = retries.lock().unwrap().clone();
+        retries.sort();
+        assert_eq!(retries.len(), 12);
+
+        for (slot, retry) in retries.iter().enumerate() {
+            assert!(*retry - start >= spacing * slot as u32);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! A retry loop that doesn't depend on `std`, leaving how a backoff period is
+//! waited out to a [Sleeper](crate::sleeper::Sleeper).
+
+use core::{cell::Cell, time::Duration};
+
+use crate::{backoff::Schedule, trace, Backoff};
+
+/// Waits out backoff periods for
+/// [retry_if_with_sleeper](crate::sleeper::retry_if_with_sleeper).
+///
+/// Any `FnMut(Duration)` implements [Sleeper](crate::sleeper::Sleeper).
+pub trait Sleeper {
+    /// Waits for at least `duration`.
+    fn sleep(&mut self, duration: Duration);
+}
+
+impl<F> Sleeper for F
+where
+    F: FnMut(Duration),
+{
+    fn sleep(&mut self, duration: Duration) {
+        self(duration)
+    }
+}
+
+/// Calls the provided function and if an error is returned it is passed to
+/// the predicate to determine if the function should be retried after the
+/// sleeper has waited out the backoff period. If the backoff gives up the
+/// error is returned.
+///
+/// This is available without the `std` feature. As no clock is available, the
+/// [elapsed](crate::backoff::BackoffContext::elapsed) time passed to the
+/// backoff is the total time spent sleeping.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let result = tryagain::retry_if_with_sleeper(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations| iterations < 3,
+///     |duration| { /* Wait using a hardware timer. */ },
+/// );
+/// # result.expect_err("expected to give up");
+/// ```
+pub fn retry_if_with_sleeper<B, F, P, S, T, E>(
+    backoff: B,
+    func: F,
+    predicate: P,
+    mut sleeper: S,
+) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+    S: Sleeper,
+{
+    let slept = Cell::new(Duration::from_secs(0));
+    let wait = |duration| {
+        sleeper.sleep(duration);
+        slept.set(slept.get().saturating_add(duration));
+    };
+
+    retry_loop(backoff, func, predicate, |_: &E, _, _| {}, wait, || slept.get(), None)
+}
+
+/// The retry loop shared by the sync retry functions. `notify` is called with
+/// each error that will be retried before `wait` is called to wait out the
+/// backoff period, `elapsed` returns the time elapsed since the first attempt
+/// and `name` labels the emitted metrics.
+pub(crate) fn retry_loop<B, F, P, N, W, L, T, E>(
+    mut backoff: B,
+    mut func: F,
+    mut predicate: P,
+    mut notify: N,
+    mut wait: W,
+    elapsed: L,
+    name: Option<&'static str>,
+) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+    N: FnMut(&E, u32, Duration),
+    W: FnMut(Duration),
+    L: Fn() -> Duration,
+{
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(name);
+
+        match result {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if !predicate(&e, iterations) {
+                    trace::gave_up(name);
+                    return Err(e);
+                }
+
+                let duration = match schedule.next(&mut backoff, iterations, elapsed()) {
+                    Some(duration) => duration,
+                    None => {
+                        trace::gave_up(name);
+                        return Err(e);
+                    }
+                };
+                trace::retrying(name, iterations, duration);
+                notify(&e, iterations, duration);
+                wait(duration);
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImmediateBackoff, MinimumBackoff};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_stub_sleeper() {
+        let mut slept = Vec::new();
+        let result = retry_if_with_sleeper(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1)),
+            || Err::<(), _>(()),
+            |_, iterations| iterations < 2,
+            |duration| slept.push(duration),
+        );
+
+        assert_eq!(result, Err(()));
+        assert_eq!(slept, [Duration::from_secs(1); 2]);
+    }
+}
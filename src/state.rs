@@ -0,0 +1,265 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+#[cfg(not(all(feature = "runtime-wasm", target_arch = "wasm32")))]
+use std::time::Instant;
+#[cfg(all(feature = "runtime-wasm", target_arch = "wasm32"))]
+use web_time::Instant;
+
+use crate::{backoff::Schedule, trace, Backoff};
+
+/// Observes the failed attempts of a
+/// [RetryFuture](crate::future::RetryFuture) or
+/// [RetryState](crate::state::RetryState).
+///
+/// Any `FnMut(&E, u32, Duration)` implements [Notify](crate::state::Notify).
+pub trait Notify<E> {
+    /// Called after a failed attempt with the error, the number of iterations
+    /// and the backoff period that will be waited before the next attempt.
+    fn notify(&mut self, error: &E, iterations: u32, duration: Duration);
+}
+
+impl<E, F> Notify<E> for F
+where
+    F: FnMut(&E, u32, Duration),
+{
+    fn notify(&mut self, error: &E, iterations: u32, duration: Duration) {
+        self(error, iterations, duration)
+    }
+}
+
+/// A [Notify](crate::state::Notify) implementation that does nothing, used
+/// when no notify function was provided.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoNotify;
+
+impl<E> Notify<E> for NoNotify {
+    fn notify(&mut self, _error: &E, _iterations: u32, _duration: Duration) {}
+}
+
+/// The state machine of an asynchronous retry, without any timer, for
+/// executors that wait out the backoff period themselves.
+/// [RetryFuture](crate::future::RetryFuture) is built on top of it.
+///
+/// The current attempt is driven with
+/// [poll_attempt](crate::state::RetryState::poll_attempt). When an attempt
+/// fails and should be retried it returns [Poll::Pending] and
+/// [next_wait](crate::state::RetryState::next_wait) returns the backoff period
+/// that must be waited before polling again. The current attempt is stored
+/// inline, so the state has to be pinned before it can be polled.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::task::{Context, Poll};
+/// # fn drive(cx: &mut Context<'_>) {
+/// async fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let mut state = Box::pin(RetryState::new(ExponentialBackoff::default(), returns_err, |_, iterations| iterations < 3));
+///
+/// let result = loop {
+///     match state.as_mut().poll_attempt(cx) {
+///         Poll::Ready(result) => break result,
+///         Poll::Pending => match state.next_wait() {
+///             Some(duration) => std::thread::sleep(duration),
+///             None => { /* wait for the attempt to wake the task */ }
+///         },
+///     }
+/// };
+/// # }
+/// ```
+#[pin_project::pin_project]
+pub struct RetryState<F, Fut, P, B, N = NoNotify> {
+    factory: F,
+    #[pin]
+    future: Fut,
+    predicate: P,
+    backoff: B,
+    notify: N,
+    started: Instant,
+    schedule: Schedule,
+    iterations: u32,
+    next_wait: Option<Duration>,
+    name: Option<&'static str>,
+}
+
+impl<F, Fut, P, B: fmt::Debug, N> fmt::Debug for RetryState<F, Fut, P, B, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryState")
+            .field("iterations", &self.iterations)
+            .field("next_wait", &self.next_wait)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, Fut, P, B> RetryState<F, Fut, P, B> {
+    /// Creates a [RetryState](crate::state::RetryState), calling `func` to
+    /// start the first attempt. The predicate works the same as the one passed
+    /// to [retry_if](crate::future::retry_if).
+    pub fn new<T, E>(backoff: B, func: F, predicate: P) -> Self
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        P: Fn(&E, u32) -> bool,
+        B: Backoff,
+    {
+        Self::with_notify(backoff, func, predicate, NoNotify)
+    }
+}
+
+impl<F, Fut, P, B, N> RetryState<F, Fut, P, B, N> {
+    pub(crate) fn with_notify(mut backoff: B, mut func: F, predicate: P, notify: N) -> Self
+    where
+        F: FnMut() -> Fut,
+        B: Backoff,
+    {
+        let future = func();
+        let schedule = Schedule::start(&mut backoff);
+
+        Self {
+            factory: func,
+            future,
+            predicate,
+            backoff,
+            notify,
+            started: Instant::now(),
+            schedule,
+            iterations: 0,
+            next_wait: None,
+            name: None,
+        }
+    }
+
+    /// Sets the name of the operation being retried, used to label the
+    /// metrics emitted with the `metrics` feature.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// The backoff period that must be waited before the next call to
+    /// [poll_attempt](crate::state::RetryState::poll_attempt), if the last
+    /// attempt failed and is being retried.
+    pub fn next_wait(&self) -> Option<Duration> {
+        self.next_wait
+    }
+
+    /// The number of attempts that have failed so far.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+}
+
+impl<T, E, F, Fut, P, B, N> RetryState<F, Fut, P, B, N>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E, u32) -> bool,
+    B: Backoff,
+    N: Notify<E>,
+{
+    /// Polls the current attempt, resolving once it succeeds or fails without
+    /// being retried.
+    ///
+    /// If the attempt is still running [Poll::Pending] is returned and the
+    /// task is woken as usual. If it failed and will be retried the next
+    /// attempt is started, [Poll::Pending] is returned without waking the task
+    /// and [next_wait](crate::state::RetryState::next_wait) returns how long
+    /// to wait before polling again.
+    pub fn poll_attempt(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<T, E>> {
+        let mut this = self.project();
+        *this.next_wait = None;
+
+        let result = match this.future.as_mut().poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        trace::attempted(*this.name);
+
+        let e = match result {
+            Ok(value) => return Poll::Ready(Ok(value)),
+            Err(e) => e,
+        };
+
+        *this.iterations += 1;
+        if !(this.predicate)(&e, *this.iterations) {
+            trace::gave_up(*this.name);
+            return Poll::Ready(Err(e));
+        }
+
+        let elapsed = this.started.elapsed();
+        let duration = match this.schedule.next(this.backoff, *this.iterations, elapsed) {
+            Some(duration) => duration,
+            None => {
+                trace::gave_up(*this.name);
+                return Poll::Ready(Err(e));
+            }
+        };
+        trace::retrying(*this.name, *this.iterations, duration);
+        this.notify.notify(&e, *this.iterations, duration);
+
+        this.future.set((this.factory)());
+        *this.next_wait = Some(duration);
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImmediateBackoff, MinimumBackoff};
+    use std::{
+        cell::Cell,
+        sync::Arc,
+        task::{Wake, Waker},
+    };
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn test_drive_by_hand() {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let period = Duration::from_secs(10);
+        let attempts = Cell::new(0);
+
+        let mut state = Box::pin(RetryState::new(
+            MinimumBackoff::new(ImmediateBackoff, period),
+            || {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move {
+                    if attempt < 2 {
+                        Err(attempt)
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            |_, _| true,
+        ));
+        assert_eq!(state.next_wait(), None);
+
+        assert_eq!(state.as_mut().poll_attempt(&mut cx), Poll::Pending);
+        assert_eq!(state.next_wait(), Some(period));
+        assert_eq!(state.iterations(), 1);
+
+        // The caller is responsible for waiting, no time has to pass.
+        assert_eq!(state.as_mut().poll_attempt(&mut cx), Poll::Ready(Ok(2)));
+        assert_eq!(state.next_wait(), None);
+        assert_eq!(attempts.get(), 2);
+    }
+}
@@ -1,4 +1,20 @@
-use crate::Backoff;
+use std::{
+    any::Any,
+    fmt,
+    convert::Infallible,
+    ops::ControlFlow,
+    panic::{self, RefUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backoff::Schedule, sleeper::retry_loop, trace, Backoff, HintedBackoff, Now, RetryDelayHint, Retryable, Sleeper,
+};
 
 /// Retries the provided function if it returns an error whenever the backoff
 /// allows. The first call resulting in success will have it's value returned
@@ -17,14 +33,420 @@ use crate::Backoff;
 /// let value = retry(ExponentialBackoff::default(), returns_err);
 /// # assert_eq!(value, ());
 /// ```
+///
+/// This never gives up, a backoff that would give up keeps waiting the delay
+/// it computes instead. Use [retry_if](crate::sync::retry_if) or
+/// [retry_bounded](crate::sync::retry_bounded) to stop and get the last error.
 pub fn retry<B, F, T, E>(backoff: B, func: F) -> T
 where
     B: Backoff,
-    F: Fn() -> Result<T, E>,
+    F: FnMut() -> Result<T, E>,
 {
-    match retry_if(backoff, func, |_, _| true) {
+    // Retrying is never broken off, so the error type is uninhabited and no
+    // error can be returned.
+    match retry_with(backoff, func, |e, _| ControlFlow::<Infallible, E>::Continue(e)) {
         Ok(value) => value,
-        Err(_) => unreachable!(),
+        Err(never) => match never {},
+    }
+}
+
+/// Retries the provided function until it succeeds or `max_attempts` attempts
+/// have been made, returning the last error instead of retrying forever. The
+/// last error is also returned if the backoff gives up first.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let result = tryagain::retry_bounded(ExponentialBackoff::default(), returns_err, 3);
+/// # assert_eq!(result, Err(()));
+/// ```
+pub fn retry_bounded<B, F, T, E>(backoff: B, func: F, max_attempts: u32) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+{
+    retry_if(backoff, func, |_, iterations| iterations + 1 < max_attempts)
+}
+
+/// Calls the provided function at most `n` times, retrying whenever the
+/// backoff allows, and returns the error of the final attempt if none of them
+/// succeeded. The error is also returned if the backoff gives up first.
+///
+/// `n` is the total number of attempts including the first one, not the
+/// number of retries, so an `n` of `1` never retries. An `n` of `0` is treated
+/// as `1`, the function is always called at least once.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// // Calls returns_err 5 times.
+/// let result = tryagain::retry_n(ImmediateBackoff, 5, returns_err);
+/// # assert_eq!(result, Err(()));
+/// ```
+pub fn retry_n<B, F, T, E>(backoff: B, n: u32, func: F) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+{
+    retry_bounded(backoff, func, n)
+}
+
+/// The same as [retry](crate::sync::retry) but the function is passed the
+/// zero-based number of the attempt it is making, the same iteration count
+/// that predicates are passed after it fails.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// let value = tryagain::retry_enumerated(ImmediateBackoff, |attempt| {
+///     if attempt < 2 {
+///         Err(())
+///     } else {
+///         Ok(attempt)
+///     }
+/// });
+/// # assert_eq!(value, 2);
+/// ```
+///
+/// Like [retry](crate::sync::retry) this never gives up, even if the backoff
+/// would.
+pub fn retry_enumerated<B, F, T, E>(backoff: B, mut func: F) -> T
+where
+    B: Backoff,
+    F: FnMut(u32) -> Result<T, E>,
+{
+    let mut attempt = 0;
+
+    retry(backoff, || {
+        let result = func(attempt);
+        attempt += 1;
+        result
+    })
+}
+
+/// Retries the provided function while it returns `None` whenever the backoff
+/// allows, returning the value of the first `Some`.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::collections::VecDeque;
+/// let mut queue = VecDeque::from(vec![None, None, Some(42)]);
+///
+/// let value = tryagain::retry_option(ImmediateBackoff, || queue.pop_front().flatten());
+/// # assert_eq!(value, 42);
+/// ```
+///
+/// Like [retry](crate::sync::retry) this never gives up, even if the backoff
+/// would.
+pub fn retry_option<B, F, T>(backoff: B, mut func: F) -> T
+where
+    B: Backoff,
+    F: FnMut() -> Option<T>,
+{
+    retry(backoff, || func().ok_or(()))
+}
+
+/// Retries the provided function while it returns a value that isn't `done`
+/// yet whenever the backoff allows, returning the first value for which `done`
+/// returns true. Errors aren't retried and are returned immediately.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::cell::Cell;
+/// #[derive(Debug, PartialEq)]
+/// enum Status {
+///     Pending,
+///     Finished,
+/// }
+///
+/// let polls = Cell::new(0);
+/// let poll_job = || {
+///     polls.set(polls.get() + 1);
+///
+///     if polls.get() < 3 {
+///         Ok::<_, ()>(Status::Pending)
+///     } else {
+///         Ok(Status::Finished)
+///     }
+/// };
+///
+/// let status = tryagain::retry_until_value(ImmediateBackoff, poll_job, |status| *status == Status::Finished);
+/// # assert_eq!(status, Ok(Status::Finished));
+/// ```
+///
+/// Like [retry](crate::sync::retry) this never gives up, even if the backoff
+/// would.
+pub fn retry_until_value<B, F, D, T, E>(backoff: B, mut func: F, done: D) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    D: Fn(&T) -> bool,
+{
+    retry_with(
+        backoff,
+        || match func() {
+            Ok(value) if done(&value) => Ok(value),
+            Ok(_) => Err(None),
+            Err(e) => Err(Some(e)),
+        },
+        |e, _| match e {
+            Some(e) => ControlFlow::Break(e),
+            None => ControlFlow::Continue(None),
+        },
+    )
+}
+
+/// The same as [retry](crate::sync::retry) but also returns the total time
+/// spent from the first attempt until the successful one, including time spent
+/// waiting for the backoff.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_ok() -> Result<u32, ()> {
+///     Ok(42)
+/// }
+///
+/// let (value, elapsed) = tryagain::retry_timed(ExponentialBackoff::default(), returns_ok);
+/// # assert_eq!(value, 42);
+/// ```
+pub fn retry_timed<B, F, T, E>(backoff: B, func: F) -> (T, Duration)
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+{
+    let start = Instant::now();
+    let value = retry(backoff, func);
+
+    (value, start.elapsed())
+}
+
+/// The same as [retry](crate::sync::retry) but also returns every error that
+/// was retried past before the function succeeded, in the order they occurred.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::cell::Cell;
+/// let attempts = Cell::new(0);
+/// let (value, history) = tryagain::retry_with_history(ImmediateBackoff, || {
+///     attempts.set(attempts.get() + 1);
+///
+///     if attempts.get() < 3 {
+///         Err(attempts.get())
+///     } else {
+///         Ok("done")
+///     }
+/// });
+/// # assert_eq!(value, "done");
+/// # assert_eq!(history, [1, 2]);
+/// ```
+///
+/// Like [retry](crate::sync::retry) this never gives up, even if the backoff
+/// would.
+pub fn retry_with_history<B, F, T, E>(mut backoff: B, mut func: F) -> (T, Vec<E>)
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut history = Vec::new();
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        match result {
+            Ok(value) => return (value, history),
+            Err(e) => {
+                let duration = schedule.compute(&mut backoff, iterations, start.elapsed());
+                history.push(e);
+                trace::retrying(None, iterations, duration);
+                wait(duration);
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// The same as [retry_with_history](crate::sync::retry_with_history) but
+/// collapses runs of identical consecutive errors into a single entry, along
+/// with the number of times it was repeated.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::cell::Cell;
+/// let attempts = Cell::new(0);
+/// let (value, history) = tryagain::retry_with_dedup_history(ImmediateBackoff, || {
+///     attempts.set(attempts.get() + 1);
+///
+///     if attempts.get() < 4 {
+///         Err("unavailable")
+///     } else {
+///         Ok("done")
+///     }
+/// });
+/// # assert_eq!(value, "done");
+/// # assert_eq!(history, [("unavailable", 3)]);
+/// ```
+///
+/// Like [retry](crate::sync::retry) this never gives up, even if the backoff
+/// would.
+pub fn retry_with_dedup_history<B, F, T, E>(mut backoff: B, mut func: F) -> (T, Vec<(E, u32)>)
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    E: PartialEq,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut history: Vec<(E, u32)> = Vec::new();
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        match result {
+            Ok(value) => return (value, history),
+            Err(e) => {
+                let duration = schedule.compute(&mut backoff, iterations, start.elapsed());
+                match history.last_mut() {
+                    Some((last, count)) if *last == e => *count += 1,
+                    _ => history.push((e, 1)),
+                }
+                trace::retrying(None, iterations, duration);
+                wait(duration);
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but when retrying stops
+/// every error encountered is returned in the order they occurred, up to
+/// `limit`, instead of only the final one.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::io::ErrorKind;
+/// let mut errors = vec![ErrorKind::TimedOut, ErrorKind::NotFound, ErrorKind::ConnectionRefused];
+///
+/// let result = tryagain::retry_if_collect(
+///     ImmediateBackoff,
+///     || Err::<(), _>(errors.pop().unwrap()),
+///     |_error, iterations| iterations < 2,
+///     HistoryLimit::Last(10),
+/// );
+/// # assert_eq!(result, Err(vec![ErrorKind::ConnectionRefused, ErrorKind::NotFound, ErrorKind::TimedOut]));
+/// ```
+pub fn retry_if_collect<B, F, P, T, E>(
+    mut backoff: B,
+    mut func: F,
+    mut predicate: P,
+    limit: HistoryLimit,
+) -> Result<T, Vec<E>>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut history = History::new(limit);
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        let e = match result {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        let next = if predicate(&e, iterations) {
+            schedule.next(&mut backoff, iterations, start.elapsed())
+        } else {
+            None
+        };
+        history.push(e);
+
+        match next {
+            Some(duration) => {
+                trace::retrying(None, iterations, duration);
+                wait(duration);
+            }
+            None => {
+                trace::gave_up(None);
+                return Err(history.into_vec());
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// How many of the errors encountered by
+/// [retry_if_collect](crate::sync::retry_if_collect) are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryLimit {
+    /// Keeps every error. The history grows with every attempt, so the
+    /// predicate or backoff should eventually give up.
+    All,
+    /// Keeps the first `n` errors, the final error is dropped if more than
+    /// `n` attempts were made.
+    First(usize),
+    /// Keeps the last `n` errors, which include the final error.
+    Last(usize),
+}
+
+/// The errors collected by a retry, bounded by a
+/// [HistoryLimit](crate::sync::HistoryLimit).
+pub(crate) struct History<E> {
+    errors: std::collections::VecDeque<E>,
+    limit: HistoryLimit,
+}
+
+impl<E> History<E> {
+    pub(crate) fn new(limit: HistoryLimit) -> Self {
+        Self {
+            errors: std::collections::VecDeque::new(),
+            limit,
+        }
+    }
+
+    pub(crate) fn push(&mut self, error: E) {
+        match self.limit {
+            HistoryLimit::First(n) | HistoryLimit::Last(n) if n == 0 => return,
+            HistoryLimit::First(n) if self.errors.len() >= n => return,
+            HistoryLimit::Last(n) if self.errors.len() >= n => {
+                self.errors.pop_front();
+            }
+            _ => {}
+        }
+
+        self.errors.push_back(error);
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<E> {
+        self.errors.into()
     }
 }
 
@@ -55,26 +477,2577 @@ where
 /// );
 /// # result.expect_err("expected fatal error from result");
 /// ```
-pub fn retry_if<B, F, P, T, E>(mut backoff: B, func: F, predicate: P) -> Result<T, E>
+pub fn retry_if<B, F, P, T, E>(backoff: B, func: F, predicate: P) -> Result<T, E>
 where
     B: Backoff,
-    F: Fn() -> Result<T, E>,
-    P: Fn(&E, u32) -> bool,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
 {
-    let mut iterations = 0;
+    retry_if_with_parker(backoff, func, predicate, wait)
+}
 
-    loop {
-        match func() {
-            Ok(value) => return Ok(value),
-            Err(e) => {
-                if !predicate(&e, iterations) {
-                    return Err(e);
-                }
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows, calling `notify` with the error, the number of iterations and the
+/// backoff period after every failed attempt, before waiting. `notify` isn't
+/// called for the final error that is returned.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+/// #   return Ok(()); // Hack so our doc-tests pass
+///     Err(())
+/// }
+///
+/// let value = tryagain::retry_notify(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations, duration| println!("attempt {} failed, retrying in {:?}", iterations, duration),
+/// );
+/// # assert_eq!(value, Ok(()));
+/// ```
+pub fn retry_notify<B, F, N, T, E>(backoff: B, func: F, notify: N) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    N: FnMut(&E, u32, Duration),
+{
+    retry_if_notify(backoff, func, |_, _| true, notify)
+}
 
-                std::thread::sleep(backoff.backoff_period(iterations));
-            }
-        }
+/// The same as [retry_if](crate::sync::retry_if) but `notify` is called with
+/// the error, the number of iterations and the backoff period once the
+/// predicate has decided to retry, before waiting. `notify` isn't called for
+/// the final error that is returned.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), std::io::Error> {
+///     Err(std::io::ErrorKind::ConnectionRefused.into())
+/// }
+///
+/// let result = tryagain::retry_if_notify(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations| iterations < 3,
+///     |error, iterations, duration| eprintln!("attempt {} failed: {}, retrying in {:?}", iterations, error, duration),
+/// );
+/// # result.expect_err("expected to give up");
+/// ```
+pub fn retry_if_notify<B, F, P, N, T, E>(backoff: B, func: F, predicate: P, notify: N) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+    N: FnMut(&E, u32, Duration),
+{
+    let start = Instant::now();
+    retry_loop(backoff, func, predicate, notify, wait, || start.elapsed(), None)
+}
 
-        iterations += 1;
+/// Retries the provided function until it succeeds or `max_attempts` attempts
+/// have been made, after which `fallback` is called with the final error and
+/// its result is returned instead.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn fetch_from_primary() -> Result<&'static str, ()> {
+///     Err(())
+/// }
+///
+/// let value = tryagain::retry_or_else(ImmediateBackoff, fetch_from_primary, 3, |_error| Ok("cached"));
+/// # assert_eq!(value, Ok("cached"));
+/// ```
+pub fn retry_or_else<B, F, O, T, E>(backoff: B, func: F, max_attempts: u32, fallback: O) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    O: FnOnce(E) -> Result<T, E>,
+{
+    retry_bounded(backoff, func, max_attempts).or_else(fallback)
+}
+
+/// Retries the provided function whenever the backoff allows as long as the
+/// returned error is [retryable](crate::Retryable::is_retryable).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// use std::io::{self, ErrorKind};
+///
+/// fn read_config() -> io::Result<String> {
+///     Err(ErrorKind::NotFound.into())
+/// }
+///
+/// // A missing file isn't transient, so it isn't retried.
+/// let result = tryagain::retry_retryable(ExponentialBackoff::default(), read_config);
+/// # assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+/// ```
+pub fn retry_retryable<B, F, T, E>(backoff: B, func: F) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    E: Retryable,
+{
+    retry_if(backoff, func, |error, _| error.is_retryable())
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but the error that stops the
+/// retrying is converted with `map`, which is also passed the iteration count
+/// of the final attempt. The predicate still receives the original error.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// #[derive(Debug)]
+/// struct ConnectionError {
+///     attempts: u32,
+/// }
+///
+/// fn returns_err() -> Result<(), std::io::Error> {
+///     Err(std::io::ErrorKind::ConnectionRefused.into())
+/// }
+///
+/// let result = tryagain::retry_if_map_err(
+///     ImmediateBackoff,
+///     returns_err,
+///     |_error, iterations| iterations < 3,
+///     |_error, iterations| ConnectionError { attempts: iterations + 1 },
+/// );
+/// # assert_eq!(result.unwrap_err().attempts, 4);
+/// ```
+pub fn retry_if_map_err<B, F, P, M, T, E, E2>(backoff: B, func: F, mut predicate: P, map: M) -> Result<T, E2>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+    M: Fn(E, u32) -> E2,
+{
+    let iterations = std::cell::Cell::new(0);
+
+    retry_if(backoff, func, |error, current| {
+        iterations.set(current);
+        predicate(error, current)
+    })
+    .map_err(|error| map(error, iterations.get()))
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but each error is passed to
+/// the [HintedBackoff](crate::backoff::HintedBackoff), so a delay requested by
+/// the error is waited for instead of the inner backoff's.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// struct TooManyRequests {
+///     retry_after: Option<Duration>,
+/// }
+///
+/// fn returns_err() -> Result<(), TooManyRequests> {
+///     Err(TooManyRequests {
+///         retry_after: Some(Duration::from_millis(10)),
+///     })
+/// }
+///
+/// let backoff = HintedBackoff::new(ExponentialBackoff::default(), |error: &TooManyRequests| {
+///     error.retry_after
+/// });
+/// let result = tryagain::retry_if_hinted(backoff, returns_err, |_error, iterations| iterations < 3);
+/// # assert!(result.is_err());
+/// ```
+pub fn retry_if_hinted<B, H, F, P, T, E>(
+    mut backoff: HintedBackoff<B, H>,
+    mut func: F,
+    mut predicate: P,
+) -> Result<T, E>
+where
+    B: Backoff,
+    H: Fn(&E) -> Option<Duration>,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        match result {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if !predicate(&e, iterations) {
+                    trace::gave_up(None);
+                    return Err(e);
+                }
+
+                let duration = match schedule.next_with(iterations, start.elapsed(), |ctx| {
+                    backoff.next_backoff_for(&e, ctx)
+                }) {
+                    Some(duration) => duration,
+                    None => {
+                        trace::gave_up(None);
+                        return Err(e);
+                    }
+                };
+                trace::retrying(None, iterations, duration);
+                wait(duration);
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but when an error has a
+/// [retry_after](crate::RetryDelayHint::retry_after) hint it is waited for
+/// instead of the backoff's delay.
+///
+/// The hint replaces the delay entirely, including the minimum of a
+/// [MinimumBackoff](crate::backoff::MinimumBackoff), while the backoff can
+/// still give up. See [HintedBackoff](crate::backoff::HintedBackoff) for
+/// errors that don't implement [RetryDelayHint](crate::RetryDelayHint).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// struct TooManyRequests {
+///     retry_after: Option<Duration>,
+/// }
+///
+/// impl RetryDelayHint for TooManyRequests {
+///     fn retry_after(&self) -> Option<Duration> {
+///         self.retry_after
+///     }
+/// }
+///
+/// fn returns_err() -> Result<(), TooManyRequests> {
+///     Err(TooManyRequests {
+///         retry_after: Some(Duration::from_millis(10)),
+///     })
+/// }
+///
+/// let result = tryagain::retry_with_hints(ExponentialBackoff::default(), returns_err, |_error, iterations| iterations < 3);
+/// # assert!(result.is_err());
+/// ```
+pub fn retry_with_hints<B, F, P, T, E>(backoff: B, func: F, predicate: P) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+    E: RetryDelayHint,
+{
+    retry_if_hinted(HintedBackoff::new(backoff, E::retry_after), func, predicate)
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but also returns
+/// [RetryStats](crate::sync::RetryStats) describing the attempts that were
+/// made.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let (result, stats) = tryagain::retry_instrumented(
+///     ImmediateBackoff,
+///     returns_err,
+///     |_error, iterations| iterations < 2,
+/// );
+/// # assert_eq!(result, Err(()));
+/// # assert_eq!(stats.attempts, 3);
+/// ```
+pub fn retry_instrumented<B, F, P, T, E>(backoff: B, mut func: F, mut predicate: P) -> (Result<T, E>, RetryStats)
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    let attempts = std::cell::Cell::new(0);
+    let last_attempt = std::cell::Cell::new(Duration::ZERO);
+    let last_error_iteration = std::cell::Cell::new(None);
+    let mut total_sleep = Duration::ZERO;
+
+    let result = retry_if_with_parker(
+        backoff,
+        || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() > 1 {
+                last_attempt.set(start.elapsed());
+            }
+            func()
+        },
+        |error, iterations| {
+            last_error_iteration.set(Some(iterations));
+            predicate(error, iterations)
+        },
+        |duration| {
+            total_sleep = total_sleep.saturating_add(duration);
+            wait(duration);
+        },
+    );
+
+    let stats = RetryStats {
+        attempts: attempts.get(),
+        total_sleep,
+        elapsed: start.elapsed(),
+        last_attempt: last_attempt.get(),
+        last_error_iteration: last_error_iteration.get(),
+    };
+
+    (result, stats)
+}
+
+/// The same as [retry](crate::sync::retry) but also returns
+/// [RetryStats](crate::sync::RetryStats) describing what the result cost,
+/// along with the value on success or the last error if the backoff gave up.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_ok() -> Result<u32, ()> {
+///     Ok(42)
+/// }
+///
+/// let (value, stats) = tryagain::retry_with_stats(ExponentialBackoff::default(), returns_ok).unwrap();
+/// println!("took {} attempts and {:?} of backoff", stats.attempts, stats.total_sleep);
+/// # assert_eq!(stats.attempts, 1);
+/// ```
+pub fn retry_with_stats<B, F, T, E>(backoff: B, func: F) -> Result<(T, RetryStats), (E, RetryStats)>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+{
+    match retry_instrumented(backoff, func, |_, _| true) {
+        (Ok(value), stats) => Ok((value, stats)),
+        (Err(e), stats) => Err((e, stats)),
+    }
+}
+
+/// Statistics about the attempts made by
+/// [retry_instrumented](crate::sync::retry_instrumented) and
+/// [retry_with_stats](crate::sync::retry_with_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryStats {
+    /// The number of times the function was called.
+    pub attempts: u32,
+    /// The total time spent waiting for the backoff between attempts.
+    pub total_sleep: Duration,
+    /// The total time from the first attempt until retrying finished.
+    pub elapsed: Duration,
+    /// The time from the first attempt until the final attempt started, zero
+    /// if only one attempt was made.
+    pub last_attempt: Duration,
+    /// The iteration of the last attempt that returned an error, if any.
+    pub last_error_iteration: Option<u32>,
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but returns an
+/// [Outcome](crate::sync::Outcome) that also carries the number of attempts
+/// that were made.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), &'static str> {
+///     Err("unavailable")
+/// }
+///
+/// match tryagain::retry_outcome(ImmediateBackoff, returns_err, |_error, iterations| iterations < 2) {
+///     Outcome::Success { value, attempts } => println!("got {:?} after {} attempts", value, attempts),
+///     Outcome::GaveUp { error, attempts } => println!("gave up after {} attempts: {}", attempts, error),
+/// }
+/// ```
+pub fn retry_outcome<B, F, P, T, E>(backoff: B, mut func: F, predicate: P) -> Outcome<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    let attempts = std::cell::Cell::new(0);
+
+    let result = retry_if(
+        backoff,
+        || {
+            attempts.set(attempts.get() + 1);
+            func()
+        },
+        predicate,
+    );
+
+    match result {
+        Ok(value) => Outcome::Success {
+            value,
+            attempts: attempts.get(),
+        },
+        Err(error) => Outcome::GaveUp {
+            error,
+            attempts: attempts.get(),
+        },
+    }
+}
+
+/// The result of [retry_outcome](crate::sync::retry_outcome).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome<T, E> {
+    /// The function succeeded.
+    Success {
+        /// The value returned by the successful attempt.
+        value: T,
+        /// The number of attempts made, including the successful one.
+        attempts: u32,
+    },
+    /// The predicate or the backoff stopped retrying.
+    GaveUp {
+        /// The error returned by the last attempt.
+        error: E,
+        /// The number of attempts made.
+        attempts: u32,
+    },
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but the error that stops the
+/// retrying is returned as a [RetryError](crate::sync::RetryError), which also
+/// carries the number of attempts made and the time spent retrying.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), &'static str> {
+///     Err("connection refused")
+/// }
+///
+/// let error = tryagain::retry_if_detailed(ImmediateBackoff, returns_err, |_error, iterations| iterations < 2).unwrap_err();
+/// println!("{} after {} attempts in {:?}", error, error.attempts(), error.elapsed());
+/// # assert_eq!(error.attempts(), 3);
+/// ```
+pub fn retry_if_detailed<B, F, P, T, E>(backoff: B, mut func: F, predicate: P) -> Result<T, RetryError<E>>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    let mut attempts = 0;
+
+    retry_if(
+        backoff,
+        || {
+            attempts += 1;
+            func()
+        },
+        predicate,
+    )
+    .map_err(|error| RetryError::new(error, attempts, start.elapsed()))
+}
+
+/// The error returned by [retry_if_detailed](crate::sync::retry_if_detailed)
+/// and [future::retry_if_detailed](crate::future::retry_if_detailed), the
+/// error of the final attempt along with how hard the function was retried.
+///
+/// It dereferences to the error of the final attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryError<E> {
+    error: E,
+    attempts: u32,
+    elapsed: Duration,
+}
+
+impl<E> RetryError<E> {
+    pub(crate) fn new(error: E, attempts: u32, elapsed: Duration) -> Self {
+        Self {
+            error,
+            attempts,
+            elapsed,
+        }
+    }
+
+    /// The error returned by the final attempt.
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    /// The number of times the function was called, including the first
+    /// attempt.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// The time from the first attempt until retrying stopped, including time
+    /// spent waiting for the backoff.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the error returned by the final attempt.
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E> std::ops::Deref for RetryError<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.error
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (after {} attempts in {:?})", self.error, self.attempts, self.elapsed)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for RetryError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but the metrics emitted with
+/// the `metrics` feature are labeled with `name` as the `operation`.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn fetch_user() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let result = tryagain::retry_if_named("fetch_user", ImmediateBackoff, fetch_user, |_error, iterations| {
+///     iterations < 3
+/// });
+/// # result.expect_err("expected to give up");
+/// ```
+pub fn retry_if_named<B, F, P, T, E>(name: &'static str, backoff: B, func: F, predicate: P) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    retry_loop(backoff, func, predicate, |_: &E, _, _| {}, wait, || start.elapsed(), Some(name))
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but the backoff period is
+/// waited out by calling `park` instead of
+/// [std::thread::sleep](std::thread::sleep). This allows green-thread runtimes
+/// to yield to other work instead of blocking the OS thread.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let result = tryagain::retry_if_with_parker(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     |_error, iterations| iterations < 3,
+///     |duration| println!("parking for {:?}", duration),
+/// );
+/// # result.expect_err("expected to give up");
+/// ```
+pub fn retry_if_with_parker<B, F, P, K, T, E>(
+    backoff: B,
+    func: F,
+    predicate: P,
+    park: K,
+) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+    K: FnMut(Duration),
+{
+    let start = Instant::now();
+    retry_loop(backoff, func, predicate, |_: &E, _, _| {}, park, || start.elapsed(), None)
+}
+
+/// Calls the provided function and if an error is returned it is passed to
+/// `decide` along with the iteration count. Returning
+/// [ControlFlow::Continue](std::ops::ControlFlow::Continue) retries the
+/// function when the backoff allows, while
+/// [ControlFlow::Break](std::ops::ControlFlow::Break) stops retrying and
+/// returns the provided error, which may be of a different type.
+///
+/// As the backoff has no mapped error to return it isn't able to give up,
+/// `decide` is solely responsible for stopping.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// use std::ops::ControlFlow;
+///
+/// #[derive(Debug)]
+/// struct GaveUp(u32);
+///
+/// fn returns_err() -> Result<(), u32> {
+///     Err(0)
+/// }
+///
+/// let result = tryagain::retry_with(
+///     ImmediateBackoff,
+///     returns_err,
+///     |error, iterations| {
+///         if iterations < 3 {
+///             ControlFlow::Continue(error)
+///         } else {
+///             ControlFlow::Break(GaveUp(iterations))
+///         }
+///     },
+/// );
+/// # result.expect_err("expected to give up");
+/// ```
+pub fn retry_with<B, F, D, T, E, E2>(mut backoff: B, mut func: F, mut decide: D) -> Result<T, E2>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    D: FnMut(E, u32) -> ControlFlow<E2, E>,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        match result {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if let ControlFlow::Break(mapped) = decide(e, iterations) {
+                    trace::gave_up(None);
+                    return Err(mapped);
+                }
+
+                let duration = schedule.compute(&mut backoff, iterations, start.elapsed());
+                trace::retrying(None, iterations, duration);
+                wait(duration);
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows, giving up with the most recent error once waiting for the next
+/// attempt would pass the deadline or the backoff gives up.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::{Duration, Instant};
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let deadline = Instant::now() + Duration::from_millis(50);
+/// let result = tryagain::retry_until(ImmediateBackoff, returns_err, deadline);
+/// # result.expect_err("expected to pass the deadline");
+/// ```
+pub fn retry_until<B, F, T, E>(backoff: B, func: F, deadline: Instant) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+{
+    retry_until_with_clock(backoff, func, deadline, Instant::now, wait)
+}
+
+/// The same as [retry_until](crate::sync::retry_until) but the current time is
+/// read from the provided [Now](crate::clock::Now) implementation instead of
+/// [Instant::now](std::time::Instant::now) and the backoff period is waited
+/// out by the provided [Sleeper](crate::sleeper::Sleeper). A mock clock
+/// should be paired with a sleeper that advances it.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::{cell::Cell, time::{Duration, Instant}};
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let now = Cell::new(Instant::now());
+/// let deadline = now.get() + Duration::from_secs(60);
+/// let result = tryagain::retry_until_with_clock(
+///     ConstantBackoff::from_millis(10_000),
+///     returns_err,
+///     deadline,
+///     || now.get(),
+///     |duration| now.set(now.get() + duration),
+/// );
+/// # result.expect_err("expected to pass the deadline");
+/// ```
+pub fn retry_until_with_clock<B, F, N, S, T, E>(
+    backoff: B,
+    func: F,
+    deadline: Instant,
+    clock: N,
+    sleeper: S,
+) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    N: Now,
+    S: Sleeper,
+{
+    retry_if_until_with_clock(backoff, func, |_, _| true, deadline, clock, sleeper)
+}
+
+/// The same as [retry_until](crate::sync::retry_until) but errors are passed
+/// to the predicate to determine if the function should be retried, the same
+/// as [retry_if](crate::sync::retry_if).
+///
+/// The function is always called at least once, even if the deadline has
+/// already passed. The backoff period is never slept past the deadline, the
+/// most recent error is returned right away instead.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::{Duration, Instant};
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let deadline = Instant::now() + Duration::from_millis(50);
+/// let result = tryagain::retry_if_until(ImmediateBackoff, returns_err, |_error, iterations| iterations < 3, deadline);
+/// # result.expect_err("expected to give up");
+/// ```
+pub fn retry_if_until<B, F, P, T, E>(backoff: B, func: F, predicate: P, deadline: Instant) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    retry_if_until_with_clock(backoff, func, predicate, deadline, Instant::now, wait)
+}
+
+/// The same as [retry_if_until](crate::sync::retry_if_until) but the current
+/// time is read from the provided [Now](crate::clock::Now) implementation and
+/// the backoff period is waited out by the provided
+/// [Sleeper](crate::sleeper::Sleeper), see
+/// [retry_until_with_clock](crate::sync::retry_until_with_clock).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::{cell::Cell, time::{Duration, Instant}};
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let now = Cell::new(Instant::now());
+/// let deadline = now.get() + Duration::from_secs(60);
+/// let result = tryagain::retry_if_until_with_clock(
+///     ConstantBackoff::from_millis(10_000),
+///     returns_err,
+///     |_error, iterations| iterations < 3,
+///     deadline,
+///     || now.get(),
+///     |duration| now.set(now.get() + duration),
+/// );
+/// # result.expect_err("expected to give up");
+/// ```
+pub fn retry_if_until_with_clock<B, F, P, N, S, T, E>(
+    mut backoff: B,
+    mut func: F,
+    mut predicate: P,
+    deadline: Instant,
+    clock: N,
+    mut sleeper: S,
+) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+    N: Now,
+    S: Sleeper,
+{
+    let start = clock.now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        match result {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if !predicate(&e, iterations) {
+                    trace::gave_up(None);
+                    return Err(e);
+                }
+
+                let now = clock.now();
+                let duration = match schedule.next(&mut backoff, iterations, now - start) {
+                    Some(duration) if now.checked_add(duration).is_some_and(|next| next < deadline) => duration,
+                    _ => {
+                        trace::gave_up(None);
+                        return Err(e);
+                    }
+                };
+
+                trace::retrying(None, iterations, duration);
+                sleeper.sleep(duration);
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows, stopping early if `cancel` is set or the backoff gives up. The
+/// backoff period is slept in increments of `chunk` so that cancellation is
+/// noticed within roughly one chunk, even during a long backoff.
+///
+/// A `chunk` shorter than a millisecond is raised to a millisecond, so a zero
+/// chunk doesn't spin for the whole backoff period.
+///
+/// If the retry is cancelled the most recent error is returned.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::{sync::atomic::AtomicBool, time::Duration};
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let cancel = AtomicBool::new(true);
+/// let result = retry_cancellable_chunked(
+///     ExponentialBackoff::default(),
+///     returns_err,
+///     &cancel,
+///     Duration::from_millis(100),
+/// );
+/// # result.expect_err("expected cancellation");
+/// ```
+pub fn retry_cancellable_chunked<B, F, T, E>(
+    mut backoff: B,
+    mut func: F,
+    cancel: &AtomicBool,
+    chunk: Duration,
+) -> Result<T, E>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+{
+    let chunk = chunk.max(MIN_CHUNK);
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        let error = match result {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        let duration = match schedule.next(&mut backoff, iterations, start.elapsed()) {
+            Some(duration) => duration,
+            None => {
+                trace::gave_up(None);
+                return Err(error);
+            }
+        };
+        trace::retrying(None, iterations, duration);
+
+        // A backoff period too large to be represented is waited out until
+        // the retry is cancelled.
+        let deadline = Instant::now().checked_add(duration);
+
+        loop {
+            if cancel.load(Ordering::SeqCst) {
+                trace::gave_up(None);
+                return Err(error);
+            }
+
+            let now = Instant::now();
+            match deadline {
+                Some(deadline) if now >= deadline => break,
+                Some(deadline) => std::thread::sleep(chunk.min(deadline - now)),
+                None => std::thread::sleep(chunk),
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// The shortest chunk slept by
+/// [retry_cancellable_chunked](crate::sync::retry_cancellable_chunked).
+const MIN_CHUNK: Duration = Duration::from_millis(1);
+
+/// Retries the provided function on a new thread if it returns an error
+/// whenever the backoff allows, returning a
+/// [SyncCancelHandle](crate::sync::SyncCancelHandle) that can stop the retry
+/// from another thread along with the
+/// [JoinHandle](std::thread::JoinHandle) of the retrying thread.
+///
+/// The backoff period is waited out on a condition variable, so the thread
+/// wakes as soon as [cancel](crate::sync::SyncCancelHandle::cancel) is called
+/// rather than sleeping through the rest of the period.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let (handle, thread) = retry_cancellable_sync(ExponentialBackoff::default(), returns_err);
+/// handle.cancel();
+///
+/// let result = thread.join().unwrap();
+/// # assert_eq!(result, Err(SyncCancellableError::Cancelled));
+/// ```
+pub fn retry_cancellable_sync<B, F, T, E>(
+    backoff: B,
+    func: F,
+) -> (SyncCancelHandle, JoinHandle<Result<T, SyncCancellableError<E>>>)
+where
+    B: Backoff + Send + 'static,
+    F: FnMut() -> Result<T, E> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    let handle = SyncCancelHandle::default();
+    let token = handle.clone();
+
+    let thread = std::thread::spawn(move || retry_if_cancellable(backoff, func, |_, _| true, &token));
+
+    (handle, thread)
+}
+
+/// The same as [retry_if](crate::sync::retry_if) but the retry can be
+/// cancelled from another thread through `handle`, returning
+/// [Cancelled](crate::sync::SyncCancellableError::Cancelled).
+///
+/// Cancelling wakes the current thread even in the middle of a backoff
+/// period, so a long backoff doesn't delay stopping.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// fn returns_err() -> Result<(), ()> {
+///     Err(())
+/// }
+///
+/// let handle = SyncCancelHandle::default();
+/// let canceller = handle.clone();
+/// std::thread::spawn(move || {
+///     std::thread::sleep(Duration::from_millis(10));
+///     canceller.cancel();
+/// });
+///
+/// let result = tryagain::retry_if_cancellable(ExponentialBackoff::default(), returns_err, |_, _| true, &handle);
+/// # assert_eq!(result, Err(SyncCancellableError::Cancelled));
+/// ```
+pub fn retry_if_cancellable<B, F, P, T, E>(
+    mut backoff: B,
+    mut func: F,
+    mut predicate: P,
+    handle: &SyncCancelHandle,
+) -> Result<T, SyncCancellableError<E>>
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    P: FnMut(&E, u32) -> bool,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        if handle.is_cancelled() {
+            return Err(SyncCancellableError::Cancelled);
+        }
+
+        let result = func();
+        trace::attempted(None);
+
+        let error = match result {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        if !predicate(&error, iterations) {
+            trace::gave_up(None);
+            return Err(SyncCancellableError::Error(error));
+        }
+
+        let duration = match schedule.next(&mut backoff, iterations, start.elapsed()) {
+            Some(duration) => duration,
+            None => {
+                trace::gave_up(None);
+                return Err(SyncCancellableError::Error(error));
+            }
+        };
+        trace::retrying(None, iterations, duration);
+
+        if handle.wait(duration) {
+            trace::gave_up(None);
+            return Err(SyncCancellableError::Cancelled);
+        }
+
+        iterations += 1;
+    }
+}
+
+/// A handle used to cancel a retry started by
+/// [retry_cancellable_sync](crate::sync::retry_cancellable_sync) or
+/// [retry_if_cancellable](crate::sync::retry_if_cancellable). Clones cancel
+/// the same retry.
+#[derive(Debug, Clone, Default)]
+pub struct SyncCancelHandle {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl SyncCancelHandle {
+    /// Cancels the retry, waking the retrying thread if it is waiting out a
+    /// backoff period.
+    pub fn cancel(&self) {
+        let (cancelled, condvar) = &*self.inner;
+        *cancelled.lock().unwrap() = true;
+        condvar.notify_all();
+    }
+
+    /// Returns `true` if [cancel](crate::sync::SyncCancelHandle::cancel) has
+    /// been called.
+    pub fn is_cancelled(&self) -> bool {
+        *self.inner.0.lock().unwrap()
+    }
+
+    /// Waits for `duration` or until the retry is cancelled, returning `true`
+    /// if it was.
+    fn wait(&self, duration: Duration) -> bool {
+        let (cancelled, condvar) = &*self.inner;
+        let guard = cancelled.lock().unwrap();
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, duration, |cancelled| !*cancelled)
+            .unwrap();
+
+        *guard
+    }
+}
+
+/// The error returned by
+/// [retry_cancellable_sync](crate::sync::retry_cancellable_sync) and
+/// [retry_if_cancellable](crate::sync::retry_if_cancellable).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncCancellableError<E> {
+    /// The retry was cancelled through its
+    /// [SyncCancelHandle](crate::sync::SyncCancelHandle).
+    Cancelled,
+    /// The predicate or the backoff stopped retrying after this error.
+    Error(E),
+}
+
+/// Calls the provided function, catching any panics, and if an error is
+/// returned or a panic is caught it is passed to the predicate to determine if
+/// the function should be retried when the backoff allows.
+///
+/// When the predicate stops retrying after a panic, the panic is not re-raised
+/// and is instead returned as [UnwindFailure::Panic](crate::sync::UnwindFailure::Panic).
+/// Callers that want the panic to continue can pass its payload to
+/// [resume_unwind](std::panic::resume_unwind).
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn panics() -> Result<(), ()> {
+///     panic!("transient failure");
+/// }
+///
+/// let result = tryagain::retry_catch_unwind(
+///     ImmediateBackoff,
+///     panics,
+///     |_failure, iterations| iterations < 2,
+/// );
+/// # assert_eq!(result.unwrap_err().panic_message(), Some("transient failure"));
+/// ```
+pub fn retry_catch_unwind<B, F, P, T, E>(
+    backoff: B,
+    func: F,
+    predicate: P,
+) -> Result<T, UnwindFailure<E>>
+where
+    B: Backoff,
+    F: Fn() -> Result<T, E> + RefUnwindSafe,
+    P: FnMut(&UnwindFailure<E>, u32) -> bool,
+{
+    let func = || match panic::catch_unwind(&func) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(UnwindFailure::Error(e)),
+        Err(payload) => Err(UnwindFailure::Panic(payload)),
+    };
+
+    retry_if(backoff, func, predicate)
+}
+
+/// A failed attempt made by [retry_catch_unwind](crate::sync::retry_catch_unwind).
+pub enum UnwindFailure<E> {
+    /// The function returned an error.
+    Error(E),
+    /// The function panicked with the contained payload.
+    Panic(Box<dyn Any + Send + 'static>),
+}
+
+impl<E> UnwindFailure<E> {
+    /// Returns the panic message if this failure was caused by a panic with a
+    /// string payload.
+    pub fn panic_message(&self) -> Option<&str> {
+        match self {
+            Self::Error(_) => None,
+            Self::Panic(payload) => payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str)),
+        }
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for UnwindFailure<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(e) => f.debug_tuple("Error").field(e).finish(),
+            Self::Panic(_) => f.debug_tuple("Panic").field(&self.panic_message()).finish(),
+        }
+    }
+}
+
+/// Retries the provided function if it returns an error whenever the backoff
+/// allows, jittering each backoff period by an amount derived from hashing the
+/// error that caused it. The jittered period is between half of and the full
+/// backoff period, and identical errors always produce the same jitter.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> Result<(), &'static str> {
+/// #   return Ok(()); // So our doctests pass.
+///     Err("connection refused")
+/// }
+///
+/// let value = tryagain::retry_error_hashed_jitter(ExponentialBackoff::default(), returns_err);
+/// ```
+///
+/// Like [retry](crate::sync::retry) this never gives up, even if the backoff
+/// would.
+#[cfg(feature = "jitter")]
+pub fn retry_error_hashed_jitter<B, F, T, E>(mut backoff: B, mut func: F) -> T
+where
+    B: Backoff,
+    F: FnMut() -> Result<T, E>,
+    E: std::hash::Hash,
+{
+    let start = Instant::now();
+    let mut schedule = Schedule::start(&mut backoff);
+    let mut iterations = 0;
+
+    loop {
+        let result = func();
+        trace::attempted(None);
+
+        match result {
+            Ok(value) => return value,
+            Err(e) => {
+                let duration = schedule.compute(&mut backoff, iterations, start.elapsed());
+                let duration = crate::jitter::hashed(duration, &e);
+                trace::retrying(None, iterations, duration);
+                wait(duration);
+            }
+        }
+
+        iterations += 1;
+    }
+}
+
+/// Retries the provided function until it succeeds or `max_attempts` attempts
+/// have been made. When giving up the final error has context attached noting
+/// how many attempts were made.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// fn returns_err() -> anyhow::Result<()> {
+///     Err(anyhow::anyhow!("connection refused"))
+/// }
+///
+/// let error = tryagain::retry_anyhow(ImmediateBackoff, returns_err, 3).unwrap_err();
+/// assert_eq!(error.to_string(), "failed after 3 attempts");
+/// ```
+#[cfg(feature = "anyhow")]
+pub fn retry_anyhow<B, F, T>(backoff: B, func: F, max_attempts: u32) -> anyhow::Result<T>
+where
+    B: Backoff,
+    F: FnMut() -> anyhow::Result<T>,
+{
+    use anyhow::Context;
+
+    let attempts = std::cell::Cell::new(0);
+
+    retry_if(backoff, func, |_, iterations| {
+        attempts.set(iterations + 1);
+        iterations + 1 < max_attempts
+    })
+    .with_context(|| format!("failed after {} attempts", attempts.get()))
+}
+
+/// Waits out a backoff period on the current thread. Zero length periods yield
+/// to the scheduler instead, as sleeping for zero may spin on some platforms.
+fn wait(duration: Duration) {
+    wait_with(duration, std::thread::sleep, std::thread::yield_now)
+}
+
+fn wait_with(duration: Duration, sleep: impl FnOnce(Duration), yield_now: impl FnOnce()) {
+    if duration.is_zero() {
+        yield_now()
+    } else {
+        sleep(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackoffContext, BackoffExt, ExponentialBackoff};
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::atomic::AtomicU32,
+    };
+    use crate::{
+        ConstantBackoff, FnBackoff, ImmediateBackoff, IteratorBackoff, MinimumBackoff, OnExhausted, SliceBackoff,
+    };
+
+    #[test]
+    fn test_zero_wait_yields() {
+        let slept = Cell::new(None);
+        let yielded = Cell::new(0);
+
+        wait_with(Duration::ZERO, |duration| slept.set(Some(duration)), || yielded.set(yielded.get() + 1));
+        assert_eq!(slept.get(), None);
+        assert_eq!(yielded.get(), 1);
+
+        let period = Duration::from_millis(5);
+        wait_with(period, |duration| slept.set(Some(duration)), || yielded.set(yielded.get() + 1));
+        assert_eq!(slept.get(), Some(period));
+        assert_eq!(yielded.get(), 1);
+    }
+
+    #[test]
+    fn test_retry_n() {
+        for (n, expected) in [(0, 1), (1, 1), (2, 2), (5, 5)] {
+            let mut attempts = 0;
+            let result = retry_n(ImmediateBackoff, n, || {
+                attempts += 1;
+                Err::<(), _>(attempts)
+            });
+
+            assert_eq!(result, Err(expected));
+            assert_eq!(attempts, expected);
+        }
+
+        let mut attempts = 0;
+        let result = retry_n(ImmediateBackoff, 3, || {
+            attempts += 1;
+
+            if attempts < 3 {
+                Err(())
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn test_retry_if_detailed() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(5));
+        let error = retry_if_detailed(backoff, || Err::<(), _>("refused"), |_, iterations| iterations + 1 < 3).unwrap_err();
+
+        assert_eq!(error.attempts(), 3);
+        assert!(error.elapsed() >= Duration::from_millis(10));
+        assert_eq!(*error, "refused");
+        assert!(error.to_string().starts_with("refused (after 3 attempts in "));
+        assert_eq!(error.into_inner(), "refused");
+
+        assert_eq!(retry_if_detailed(ImmediateBackoff, || Ok::<_, ()>(1), |_, _| true), Ok(1));
+    }
+
+    #[test]
+    fn test_retry_if_collect() {
+        let collect = |limit| {
+            let mut attempts = 0;
+            retry_if_collect(
+                ImmediateBackoff,
+                || {
+                    attempts += 1;
+                    Err::<(), _>(attempts)
+                },
+                |_, iterations| iterations + 1 < 5,
+                limit,
+            )
+        };
+
+        assert_eq!(collect(HistoryLimit::All), Err(vec![1, 2, 3, 4, 5]));
+        assert_eq!(collect(HistoryLimit::First(2)), Err(vec![1, 2]));
+        assert_eq!(collect(HistoryLimit::Last(2)), Err(vec![4, 5]));
+        assert_eq!(collect(HistoryLimit::Last(0)), Err(vec![]));
+        assert_eq!(collect(HistoryLimit::First(10)), Err(vec![1, 2, 3, 4, 5]));
+
+        let backoff = SliceBackoff::new(vec![Duration::ZERO]).repeat_last(false);
+        let result = retry_if_collect(backoff, || Err::<(), _>("down"), |_, _| true, HistoryLimit::All);
+        assert_eq!(result, Err(vec!["down", "down"]));
+
+        let result = retry_if_collect(ImmediateBackoff, || Ok::<_, ()>(1), |_, _| true, HistoryLimit::All);
+        assert_eq!(result, Ok(1));
+    }
+
+    #[test]
+    fn test_retry_fn_mut() {
+        let mut attempts = 0;
+        let value = retry(ImmediateBackoff, || {
+            attempts += 1;
+
+            if attempts < 3 {
+                Err(())
+            } else {
+                Ok("done")
+            }
+        });
+
+        assert_eq!(value, "done");
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_retry_if_rotates_endpoints() {
+        let mut endpoints = vec!["c.example.com", "b.example.com", "a.example.com"];
+        let mut failures = 0;
+
+        let result = retry_if(
+            ImmediateBackoff,
+            || match endpoints.pop() {
+                Some("c.example.com") => Ok("c.example.com"),
+                Some(endpoint) => Err(endpoint),
+                None => Err("no endpoints left"),
+            },
+            |_, _| {
+                failures += 1;
+                true
+            },
+        );
+
+        assert_eq!(result, Ok("c.example.com"));
+        assert_eq!(failures, 2);
+        assert!(endpoints.is_empty());
+    }
+
+    #[test]
+    fn test_retry_with_history() {
+        let attempts = Cell::new(0);
+        let (value, history) = retry_with_history(ImmediateBackoff, || {
+            attempts.set(attempts.get() + 1);
+
+            match attempts.get() {
+                1 => Err("err0"),
+                2 => Err("err1"),
+                _ => Ok(attempts.get()),
+            }
+        });
+
+        assert_eq!(value, 3);
+        assert_eq!(history, ["err0", "err1"]);
+
+        let (value, history) = retry_with_history(ImmediateBackoff, || Ok::<_, ()>(1));
+        assert_eq!(value, 1);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_retry_until_value() {
+        let counter = Cell::new(0);
+        let value = retry_until_value(
+            ImmediateBackoff,
+            || {
+                counter.set(counter.get() + 1);
+                Ok::<_, ()>(counter.get())
+            },
+            |value| *value >= 5,
+        );
+
+        assert_eq!(value, Ok(5));
+
+        let value = retry_until_value(ImmediateBackoff, || Err::<u32, _>("failed"), |_| false);
+        assert_eq!(value, Err("failed"));
+    }
+
+    #[test]
+    fn test_retry_with_dedup_history() {
+        let attempts = Cell::new(0);
+        let (value, history) = retry_with_dedup_history(ImmediateBackoff, || {
+            attempts.set(attempts.get() + 1);
+
+            match attempts.get() {
+                1 | 2 => Err("a"),
+                3 => Err("b"),
+                _ => Ok(attempts.get()),
+            }
+        });
+
+        assert_eq!(value, 4);
+        assert_eq!(history, [("a", 2), ("b", 1)]);
+    }
+
+    #[test]
+    fn test_retry_option() {
+        let mut attempts = 0;
+        let value = retry_option(ImmediateBackoff, || {
+            attempts += 1;
+
+            if attempts < 3 {
+                None
+            } else {
+                Some(42)
+            }
+        });
+
+        assert_eq!(value, 42);
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_retry_bounded() {
+        let attempts = Cell::new(0);
+        let result = retry_bounded(
+            ImmediateBackoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(attempts.get())
+            },
+            4,
+        );
+
+        assert_eq!(result, Err(4));
+        assert_eq!(attempts.get(), 4);
+    }
+
+    #[test]
+    fn test_retry_outlasts_backoff_giving_up() {
+        let backoff = SliceBackoff::new(vec![Duration::ZERO]).repeat_last(false);
+        let mut attempts = 0;
+
+        let value = retry(backoff, || {
+            attempts += 1;
+
+            if attempts < 5 {
+                Err(())
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(value, 5);
+
+        let backoff = ExponentialBackoff::with_base(2.0).with_max(Duration::ZERO).with_max_elapsed(Duration::ZERO);
+        let mut attempts = 0;
+
+        let (value, history) = retry_with_history(backoff, || {
+            attempts += 1;
+
+            if attempts < 3 {
+                Err(attempts)
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(value, 3);
+        assert_eq!(history, [1, 2]);
+
+        let backoff = SliceBackoff::new(Vec::new()).repeat_last(false);
+        let mut polls = 0;
+
+        let value = retry_until_value(
+            backoff,
+            || {
+                polls += 1;
+                Ok::<_, ()>(polls)
+            },
+            |polls| *polls == 3,
+        );
+        assert_eq!(value, Ok(3));
+    }
+
+    #[test]
+    fn test_retry_enumerated() {
+        let mut attempts = Vec::new();
+        let value = retry_enumerated(ImmediateBackoff, |attempt| {
+            attempts.push(attempt);
+
+            if attempt < 2 {
+                Err(())
+            } else {
+                Ok(attempt)
+            }
+        });
+
+        assert_eq!(value, 2);
+        assert_eq!(attempts, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_retry_timed() {
+        let period = Duration::from_millis(20);
+        let attempts = Cell::new(0);
+        let (value, elapsed) = retry_timed(MinimumBackoff::new(ImmediateBackoff, period), || {
+            attempts.set(attempts.get() + 1);
+
+            if attempts.get() < 3 {
+                Err(())
+            } else {
+                Ok(attempts.get())
+            }
+        });
+
+        assert_eq!(value, 3);
+        assert!(elapsed >= period * 2);
+    }
+
+    #[test]
+    fn test_compute_receives_previous() {
+        struct DoublingBackoff(Rc<RefCell<Vec<Option<Duration>>>>);
+
+        impl Backoff for DoublingBackoff {
+            fn backoff_period(&mut self, _iterations: u32) -> Duration {
+                Duration::from_millis(1)
+            }
+
+            fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+                self.0.borrow_mut().push(ctx.previous);
+                ctx.previous.map_or(Duration::from_millis(1), |previous| previous * 2)
+            }
+        }
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let result = retry_if(DoublingBackoff(seen.clone()), || Err::<(), _>(()), |_, iterations| iterations < 3);
+
+        assert_eq!(result, Err(()));
+        assert_eq!(
+            *seen.borrow(),
+            [None, Some(Duration::from_millis(1)), Some(Duration::from_millis(2))]
+        );
+    }
+
+    #[test]
+    fn test_backoff_reset_between_retries() {
+        struct GrowingBackoff {
+            next: Duration,
+            delays: Vec<Duration>,
+        }
+
+        impl Backoff for GrowingBackoff {
+            fn backoff_period(&mut self, _iterations: u32) -> Duration {
+                let delay = self.next;
+                self.next *= 2;
+                self.delays.push(delay);
+                delay
+            }
+
+            fn reset(&mut self) {
+                self.next = Duration::from_millis(1);
+            }
+        }
+
+        let mut backoff = GrowingBackoff {
+            next: Duration::from_millis(1),
+            delays: Vec::new(),
+        };
+
+        for _ in 0..2 {
+            let result = retry_if(&mut backoff, || Err::<(), _>(()), |_, iterations| iterations < 3);
+            assert_eq!(result, Err(()));
+        }
+
+        assert_eq!(backoff.delays.len(), 6);
+        assert_eq!(backoff.delays[..3], backoff.delays[3..]);
+    }
+
+    #[test]
+    fn test_boxed_dyn_backoff() {
+        fn pick(immediate: bool) -> Box<dyn Backoff> {
+            if immediate {
+                Box::new(ImmediateBackoff)
+            } else {
+                Box::new(ExponentialBackoff::with_base(2.0))
+            }
+        }
+
+        for immediate in [true, false] {
+            let mut backoff = pick(immediate);
+            let start = Instant::now();
+            let result = retry_if(&mut *backoff, || Err::<(), _>(()), |_, iterations| iterations < 2);
+
+            assert_eq!(result, Err(()));
+            // The exponential backoff waits 100ms after the second attempt.
+            assert_eq!(start.elapsed() >= Duration::from_millis(100), !immediate);
+
+            let result = retry_if(backoff, || Ok::<_, ()>(1), |_, _| true);
+            assert_eq!(result, Ok(1));
+        }
+    }
+
+    #[test]
+    fn test_reuse_mut_backoff() {
+        let mut backoff = ExponentialBackoff::with_base(2.0).with_max(Duration::ZERO).with_max_elapsed(Duration::from_secs(1));
+
+        for _ in 0..2 {
+            let attempts = Cell::new(0);
+            let result = retry_if(
+                &mut backoff,
+                || {
+                    attempts.set(attempts.get() + 1);
+                    Err::<(), _>(attempts.get())
+                },
+                |_, iterations| iterations < 2,
+            );
+
+            assert_eq!(result, Err(3));
+        }
+    }
+
+    #[test]
+    fn test_retry_or_else() {
+        let attempts = Cell::new(0);
+        let result = retry_or_else(
+            ImmediateBackoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<u32, _>("unavailable")
+            },
+            3,
+            |error| {
+                assert_eq!(error, "unavailable");
+                Ok(42)
+            },
+        );
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn test_retry_retryable() {
+        use std::io::ErrorKind;
+
+        let attempts = Cell::new(0);
+        let result = retry_retryable(ImmediateBackoff, || {
+            attempts.set(attempts.get() + 1);
+
+            if attempts.get() < 3 {
+                Err(std::io::Error::from(ErrorKind::Interrupted))
+            } else {
+                Ok(attempts.get())
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        attempts.set(0);
+        let result = retry_retryable(ImmediateBackoff, || {
+            attempts.set(attempts.get() + 1);
+            Err::<(), _>(std::io::Error::from(ErrorKind::NotFound))
+        });
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_retry_if_map_err() {
+        let attempts = Cell::new(0);
+        let result: Result<(), String> = retry_if_map_err(
+            ImmediateBackoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(attempts.get())
+            },
+            |error: &u32, _| *error < 3,
+            |error, iterations| format!("error {} after {} iterations", error, iterations),
+        );
+
+        assert_eq!(result, Err("error 3 after 2 iterations".to_string()));
+    }
+
+    #[test]
+    fn test_retry_if_hinted() {
+        let backoff = HintedBackoff::new(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10)),
+            |error: &Option<u64>| error.map(Duration::from_millis),
+        );
+        let attempts = Cell::new(0);
+        let start = Instant::now();
+
+        let result = retry_if_hinted(
+            backoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(Some(20))
+            },
+            |_, iterations| iterations < 2,
+        );
+
+        assert_eq!(result, Err(Some(20)));
+        assert_eq!(attempts.get(), 3);
+        assert!(start.elapsed() >= Duration::from_millis(40));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_instrumented() {
+        let period = Duration::from_millis(10);
+        let attempts = Cell::new(0);
+
+        let (result, stats) = retry_instrumented(
+            MinimumBackoff::new(ImmediateBackoff, period),
+            || {
+                attempts.set(attempts.get() + 1);
+
+                if attempts.get() < 3 {
+                    Err(())
+                } else {
+                    Ok(attempts.get())
+                }
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(stats.attempts, 3);
+        assert_eq!(stats.total_sleep, period * 2);
+        assert_eq!(stats.last_error_iteration, Some(1));
+        assert!(stats.last_attempt >= period * 2);
+        assert!(stats.elapsed >= stats.last_attempt);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum HintedError {
+        TooManyRequests(Duration),
+        Unavailable,
+    }
+
+    impl RetryDelayHint for HintedError {
+        fn retry_after(&self) -> Option<Duration> {
+            match self {
+                HintedError::TooManyRequests(retry_after) => Some(*retry_after),
+                HintedError::Unavailable => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_with_hints() {
+        let hint = Duration::from_millis(60);
+        let fallback = Duration::from_millis(5);
+        let mut attempts = 0;
+        let start = Instant::now();
+
+        let result = retry_with_hints(
+            MinimumBackoff::new(ImmediateBackoff, fallback),
+            || {
+                attempts += 1;
+
+                match attempts {
+                    1 => Err(HintedError::TooManyRequests(hint)),
+                    2 => Err(HintedError::Unavailable),
+                    attempt => Ok(attempt),
+                }
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Ok(3));
+        assert!(start.elapsed() >= hint + fallback);
+    }
+
+    #[test]
+    fn test_retry_with_hints_overrides_minimum() {
+        let mut attempts = 0;
+        let start = Instant::now();
+
+        // The hint wins even when it is shorter than the minimum.
+        let result = retry_with_hints(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10)),
+            || {
+                attempts += 1;
+
+                if attempts < 3 {
+                    Err(HintedError::TooManyRequests(Duration::from_millis(1)))
+                } else {
+                    Ok(attempts)
+                }
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Ok(3));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_with_hints_backoff_gives_up() {
+        let backoff = SliceBackoff::new(vec![Duration::from_secs(10)]).repeat_last(false);
+        let mut attempts = 0;
+
+        let result = retry_with_hints(
+            backoff,
+            || {
+                attempts += 1;
+                Err::<(), _>(HintedError::TooManyRequests(Duration::from_millis(1)))
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Err(HintedError::TooManyRequests(Duration::from_millis(1))));
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn test_retry_notify() {
+        let period = Duration::from_millis(1);
+        let mut notified = Vec::new();
+        let mut attempts = 0;
+
+        let result = retry_notify(
+            MinimumBackoff::new(ImmediateBackoff, period),
+            || {
+                attempts += 1;
+
+                if attempts < 3 {
+                    Err(attempts)
+                } else {
+                    Ok(attempts)
+                }
+            },
+            |error: &u32, iterations, duration| notified.push((*error, iterations, duration)),
+        );
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(notified, [(1, 0, period), (2, 1, period)]);
+    }
+
+    #[test]
+    fn test_retry_if_notify() {
+        let events = std::cell::RefCell::new(Vec::new());
+        let mut attempts = 0;
+
+        let result = retry_if_notify(
+            ImmediateBackoff,
+            || {
+                attempts += 1;
+                Err::<(), _>(attempts)
+            },
+            |error, iterations| {
+                events.borrow_mut().push(format!("predicate {} {}", error, iterations));
+                iterations < 2
+            },
+            |error, iterations, _| events.borrow_mut().push(format!("notify {} {}", error, iterations)),
+        );
+
+        // The final error is returned without being passed to notify.
+        assert_eq!(result, Err(3));
+        assert_eq!(
+            events.into_inner(),
+            ["predicate 1 0", "notify 1 0", "predicate 2 1", "notify 2 1", "predicate 3 2"]
+        );
+    }
+
+    #[test]
+    fn test_retry_with_stats() {
+        let (value, stats) = retry_with_stats(ImmediateBackoff, || Ok::<_, ()>(1)).unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(stats.attempts, 1);
+        assert_eq!(stats.total_sleep, Duration::ZERO);
+        assert_eq!(stats.last_attempt, Duration::ZERO);
+        assert_eq!(stats.last_error_iteration, None);
+
+        let period = Duration::from_millis(5);
+        let mut attempts = 0;
+        let (value, stats) = retry_with_stats(MinimumBackoff::new(ImmediateBackoff, period), || {
+            attempts += 1;
+
+            if attempts < 4 {
+                Err(())
+            } else {
+                Ok(attempts)
+            }
+        })
+        .unwrap();
+        assert_eq!(value, 4);
+        assert_eq!(stats.attempts, 4);
+        assert_eq!(stats.total_sleep, period * 3);
+        assert!(stats.last_attempt >= period * 3);
+
+        let backoff = SliceBackoff::new(vec![Duration::ZERO]).repeat_last(false);
+        let (error, stats) = retry_with_stats(backoff, || Err::<(), _>("down")).unwrap_err();
+        assert_eq!(error, "down");
+        assert_eq!(stats.attempts, 2);
+        assert_eq!(stats.last_error_iteration, Some(1));
+    }
+
+    #[test]
+    fn test_retry_outcome() {
+        let attempts = Cell::new(0);
+        let outcome = retry_outcome(
+            ImmediateBackoff,
+            || {
+                attempts.set(attempts.get() + 1);
+
+                if attempts.get() < 3 {
+                    Err(())
+                } else {
+                    Ok("done")
+                }
+            },
+            |_, _| true,
+        );
+        assert_eq!(
+            outcome,
+            Outcome::Success {
+                value: "done",
+                attempts: 3,
+            }
+        );
+
+        let outcome = retry_outcome(ImmediateBackoff, || Err::<(), _>("fatal"), |_, iterations| iterations < 1);
+        assert_eq!(
+            outcome,
+            Outcome::GaveUp {
+                error: "fatal",
+                attempts: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_retry_if_with_parker() {
+        let mut parked = Vec::new();
+        let start = Instant::now();
+        let result = retry_if_with_parker(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10)),
+            || Err::<(), _>(()),
+            |_, iterations| iterations < 3,
+            |duration| parked.push(duration),
+        );
+
+        assert_eq!(result, Err(()));
+        assert_eq!(parked, [Duration::from_secs(10); 3]);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_if_backoff_gives_up() {
+        let backoff = ExponentialBackoff::with_base(2.0).with_max(Duration::ZERO).with_max_elapsed(Duration::from_millis(20));
+        let start = Instant::now();
+        let attempts = Cell::new(0);
+
+        let result = retry_if(
+            backoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(attempts.get())
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Err(attempts.get()));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_retry_if_returns_error_the_predicate_saw() {
+        for (max_iterations, delays) in [(2, 5), (5, 2), (3, 3)] {
+            let backoff = SliceBackoff::new(vec![Duration::ZERO; delays]).repeat_last(false);
+            let attempts = Cell::new(0);
+            let seen = RefCell::new(Vec::new());
+
+            let result = retry_if(
+                backoff,
+                || {
+                    attempts.set(attempts.get() + 1);
+                    Err::<(), _>(attempts.get())
+                },
+                |e, iterations| {
+                    seen.borrow_mut().push(*e);
+                    iterations < max_iterations
+                },
+            );
+
+            assert_eq!(result, Err(*seen.borrow().last().unwrap()));
+            assert_eq!(seen.borrow().len(), attempts.get() as usize);
+        }
+    }
+
+    #[test]
+    fn test_retry_constant() {
+        for backoff in [ConstantBackoff::from_millis(1), ConstantBackoff::new(Duration::ZERO)] {
+            let attempts = Cell::new(0);
+            let value = retry(backoff, || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 3 {
+                    Err(())
+                } else {
+                    Ok(attempts.get())
+                }
+            });
+
+            assert_eq!(value, 3);
+        }
+
+        let attempts = Cell::new(0);
+        let start = Instant::now();
+        let value = retry(Duration::from_millis(5), || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(())
+            } else {
+                Ok(attempts.get())
+            }
+        });
+
+        assert_eq!(value, 3);
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_retry_if_jitter() {
+        let attempts = Cell::new(0);
+        let start = Instant::now();
+        let result = retry_if(
+            crate::JitterBackoff::equal(ConstantBackoff::from_millis(10)),
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(attempts.get())
+            },
+            |_, iterations| iterations + 1 < 3,
+        );
+
+        assert_eq!(result, Err(3));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_retry_fn_backoff() {
+        let calls = Rc::new(Cell::new(0));
+        let backoff = FnBackoff({
+            let calls = calls.clone();
+            move |_| {
+                calls.set(calls.get() + 1);
+                Duration::ZERO
+            }
+        });
+
+        let attempts = Cell::new(0);
+        let value = retry(backoff, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(())
+            } else {
+                Ok(attempts.get())
+            }
+        });
+
+        assert_eq!(value, 3);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_retry_if_iterator_exhausted() {
+        let schedule = [Duration::ZERO; 2];
+        let attempts = Cell::new(0);
+        let result = retry_if(
+            IteratorBackoff::new(schedule).on_exhausted(OnExhausted::GiveUp),
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(attempts.get())
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Err(3));
+
+        let attempts = Cell::new(0);
+        let result = retry_if(
+            IteratorBackoff::new(schedule),
+            || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 5 {
+                    Err(())
+                } else {
+                    Ok(attempts.get())
+                }
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Ok(5));
+    }
+
+    #[test]
+    fn test_retry_combinators() {
+        let start = Instant::now();
+        let attempts = Cell::new(0);
+        let backoff = ImmediateBackoff.plus(Duration::from_millis(5)).scale(2.0);
+        let value = retry(backoff, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(())
+            } else {
+                Ok(attempts.get())
+            }
+        });
+
+        assert_eq!(value, 3);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_builtin_backoffs_never_give_up() {
+        let ctx = BackoffContext {
+            iterations: u32::MAX,
+            previous: Some(Duration::MAX),
+            elapsed: Duration::MAX,
+        };
+
+        assert_eq!(ImmediateBackoff.next_backoff(&ctx), Some(Duration::ZERO));
+        assert_eq!(
+            MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(1)).next_backoff(&ctx),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn test_max_elapsed_starts_at_first_attempt() {
+        let backoff = ExponentialBackoff::with_base(2.0).with_max(Duration::ZERO).with_max_elapsed(Duration::from_millis(20));
+        // Building the backoff ahead of time doesn't use up its budget.
+        std::thread::sleep(Duration::from_millis(30));
+
+        let attempts = Cell::new(0);
+        let result = retry_if(
+            backoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 3 {
+                    Err(())
+                } else {
+                    Ok(attempts.get())
+                }
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn test_retry_with_continue() {
+        let counter = Cell::new(0);
+        let result = retry_with(
+            ImmediateBackoff,
+            || {
+                counter.set(counter.get() + 1);
+
+                if counter.get() < 3 {
+                    Err(counter.get())
+                } else {
+                    Ok(counter.get())
+                }
+            },
+            |error, _| ControlFlow::<String, _>::Continue(error),
+        );
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn test_retry_with_break() {
+        let result: Result<(), String> = retry_with(
+            ImmediateBackoff,
+            || Err(42),
+            |error, iterations| {
+                if iterations < 2 {
+                    ControlFlow::Continue(error)
+                } else {
+                    ControlFlow::Break(format!("gave up on {} after {}", error, iterations))
+                }
+            },
+        );
+
+        assert_eq!(result, Err("gave up on 42 after 2".to_string()));
+    }
+
+    #[test]
+    fn test_retry_until_saturated_backoff() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::MAX);
+        let deadline = Instant::now() + Duration::from_secs(1);
+
+        assert_eq!(retry_until(backoff, || Err::<(), _>(()), deadline), Err(()));
+    }
+
+    #[test]
+    fn test_retry_until_with_mock_clock() {
+        let start = Instant::now();
+        let ticks = Cell::new(0);
+        let clock = || {
+            ticks.set(ticks.get() + 1);
+            start + Duration::from_secs(ticks.get())
+        };
+
+        let attempts = Cell::new(0);
+        let func = || {
+            attempts.set(attempts.get() + 1);
+            Err::<(), _>(())
+        };
+
+        let deadline = start + Duration::from_secs(4);
+        let result = retry_until_with_clock(ImmediateBackoff, func, deadline, clock, |_| {});
+
+        assert_eq!(result, Err(()));
+        assert_eq!(attempts.get(), 3);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_until_with_mock_sleeper() {
+        let start = Instant::now();
+        let now = Cell::new(start);
+        let slept = Cell::new(Duration::ZERO);
+
+        let attempts = Cell::new(0);
+        let func = || {
+            attempts.set(attempts.get() + 1);
+            Err::<(), _>(())
+        };
+
+        // Sleeping advances the mock clock instead of blocking, so the minute
+        // long deadline is reached after a few attempts in no time.
+        let deadline = start + Duration::from_secs(60);
+        let result = retry_until_with_clock(
+            ConstantBackoff::from_millis(25_000),
+            func,
+            deadline,
+            || now.get(),
+            |duration| {
+                slept.set(slept.get() + duration);
+                now.set(now.get() + duration);
+            },
+        );
+
+        assert_eq!(result, Err(()));
+        assert_eq!(attempts.get(), 3);
+        assert_eq!(slept.get(), Duration::from_secs(50));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_if_until_with_clock() {
+        let start = Instant::now();
+        let now = Cell::new(start);
+        let deadline = start + Duration::from_secs(60);
+
+        let attempts = Cell::new(0);
+        let result = retry_if_until_with_clock(
+            ConstantBackoff::from_millis(10_000),
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(attempts.get())
+            },
+            |error, _| *error < 2,
+            deadline,
+            || now.get(),
+            |duration| now.set(now.get() + duration),
+        );
+
+        // The predicate gives up before the deadline is reached.
+        assert_eq!(result, Err(2));
+        assert_eq!(now.get() - start, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_retry_if_until() {
+        let deadline = Instant::now() + Duration::from_millis(100);
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(40));
+
+        let attempts = Cell::new(0);
+        let result = retry_if_until(
+            backoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(())
+            },
+            |_, _| true,
+            deadline,
+        );
+
+        // Gives up instead of sleeping past the deadline.
+        assert_eq!(result, Err(()));
+        assert!(Instant::now() < deadline);
+        assert!(attempts.get() >= 2);
+
+        let result = retry_if_until(ImmediateBackoff, || Err::<(), _>("fatal"), |_, _| false, deadline);
+        assert_eq!(result, Err("fatal"));
+    }
+
+    #[test]
+    fn test_retry_if_until_past_deadline() {
+        let deadline = Instant::now();
+        std::thread::sleep(Duration::from_millis(1));
+
+        let attempts = Cell::new(0);
+        let result = retry_if_until(
+            ImmediateBackoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(())
+            },
+            |_, _| true,
+            deadline,
+        );
+
+        assert_eq!(result, Err(()));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_cancellable_sync() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(10));
+        let (handle, thread) = retry_cancellable_sync(backoff, || Err::<(), _>(()));
+
+        std::thread::sleep(Duration::from_millis(50));
+        let start = Instant::now();
+        handle.cancel();
+
+        assert_eq!(thread.join().unwrap(), Err(SyncCancellableError::Cancelled));
+        assert!(handle.is_cancelled());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_if_cancellable() {
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(5));
+        let handle = SyncCancelHandle::default();
+        let canceller = handle.clone();
+        let cancelled_at = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+            Instant::now()
+        });
+
+        let attempts = Cell::new(0);
+        let result = retry_if_cancellable(
+            backoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>(())
+            },
+            |_, _| true,
+            &handle,
+        );
+        let returned_at = Instant::now();
+
+        assert_eq!(result, Err(SyncCancellableError::Cancelled));
+        assert_eq!(attempts.get(), 1);
+        assert!(returned_at - cancelled_at.join().unwrap() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_if_cancellable_predicate() {
+        let handle = SyncCancelHandle::default();
+        let result = retry_if_cancellable(ImmediateBackoff, || Err::<(), _>("fatal"), |_, _| false, &handle);
+        assert_eq!(result, Err(SyncCancellableError::Error("fatal")));
+
+        handle.cancel();
+        let result = retry_if_cancellable(ImmediateBackoff, || Ok::<_, ()>(()), |_, _| true, &handle);
+        assert_eq!(result, Err(SyncCancellableError::Cancelled));
+    }
+
+    #[test]
+    fn test_cancellable_chunked() {
+        let cancel = AtomicBool::new(false);
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_secs(5));
+        let start = Instant::now();
+
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                cancel.store(true, Ordering::SeqCst);
+            });
+
+            retry_cancellable_chunked(backoff, || Err::<(), _>(()), &cancel, Duration::from_millis(100))
+        });
+
+        assert_eq!(result, Err(()));
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_cancellable_chunked_zero_chunk() {
+        let cancel = AtomicBool::new(false);
+        let backoff = MinimumBackoff::new(ImmediateBackoff, Duration::from_millis(20));
+        let mut attempts = 0;
+        let start = Instant::now();
+
+        let result = retry_cancellable_chunked(
+            backoff,
+            || {
+                attempts += 1;
+
+                if attempts < 3 {
+                    Err(())
+                } else {
+                    Ok(attempts)
+                }
+            },
+            &cancel,
+            Duration::ZERO,
+        );
+
+        assert_eq!(result, Ok(3));
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn test_catch_unwind_retries_panics() {
+        let attempts = AtomicU32::new(0);
+        let result = retry_catch_unwind(
+            ImmediateBackoff,
+            || {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("transient");
+                }
+
+                Ok::<_, ()>(attempts.load(Ordering::SeqCst))
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_catch_unwind_final_panic_is_returned() {
+        let result = retry_catch_unwind(
+            ImmediateBackoff,
+            || -> Result<(), ()> { panic!("always") },
+            |failure, iterations| {
+                assert_eq!(failure.panic_message(), Some("always"));
+                iterations < 2
+            },
+        );
+
+        assert_eq!(result.unwrap_err().panic_message(), Some("always"));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_error_hashed_jitter() {
+        let attempts = Cell::new(0);
+        let value = retry_error_hashed_jitter(ImmediateBackoff, || {
+            attempts.set(attempts.get() + 1);
+
+            match attempts.get() {
+                1 => Err("timeout"),
+                2 => Err("refused"),
+                n => Ok(n),
+            }
+        });
+
+        assert_eq!(value, 3);
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn test_retry_anyhow_context() {
+        let attempts = Cell::new(0);
+        let result: anyhow::Result<()> = retry_anyhow(
+            ImmediateBackoff,
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(anyhow::anyhow!("transient"))
+            },
+            4,
+        );
+
+        let error = result.unwrap_err();
+        let chain: Vec<String> = error.chain().map(|cause| cause.to_string()).collect();
+
+        assert_eq!(attempts.get(), 4);
+        assert_eq!(chain, ["failed after 4 attempts", "transient"]);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_event_per_failure() {
+        use std::sync::{atomic::AtomicUsize, Arc};
+        use tracing::{span, subscriber, Event, Metadata, Subscriber};
+
+        struct CountingSubscriber(Arc<AtomicUsize>);
+
+        impl Subscriber for CountingSubscriber {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, _: &Event<'_>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let events = Arc::new(AtomicUsize::new(0));
+        let counter = Cell::new(0);
+
+        subscriber::with_default(CountingSubscriber(events.clone()), || {
+            retry(ImmediateBackoff, || {
+                counter.set(counter.get() + 1);
+
+                if counter.get() < 4 {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            })
+        });
+
+        assert_eq!(events.load(Ordering::SeqCst), 3);
     }
 }
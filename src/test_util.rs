@@ -0,0 +1,161 @@
+//! Utilities for testing code built on top of `tryagain`.
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+
+use crate::Backoff;
+#[cfg(feature = "std")]
+use crate::BackoffContext;
+
+/// A [Backoff](crate::backoff::Backoff) implementation that never waits and
+/// records the iterations it was called with, so tests can assert how often
+/// an operation was retried without sleeping.
+///
+/// A mutable reference can be passed to the retry functions so the calls can
+/// be inspected afterwards.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// use tryagain::test_util::MockBackoff;
+///
+/// let mut backoff = MockBackoff::new();
+/// let result = tryagain::retry_if(&mut backoff, || Err::<(), _>(()), |_, iterations| iterations < 2);
+///
+/// assert_eq!(backoff.calls(), [0, 1]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockBackoff {
+    calls: Vec<u32>,
+}
+
+impl MockBackoff {
+    /// Creates a [MockBackoff](crate::test_util::MockBackoff) that hasn't recorded any calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the iterations the backoff was called with, in order.
+    pub fn calls(&self) -> &[u32] {
+        &self.calls
+    }
+}
+
+impl Backoff for MockBackoff {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        self.calls.push(iterations);
+        Duration::from_secs(0)
+    }
+}
+
+/// A [Backoff](crate::backoff::Backoff) implementation that forwards to
+/// another backoff and records every delay it returned, along with the number
+/// of iterations, so tests can assert the schedule a retry followed.
+///
+/// Clones share the same records, so a clone can be kept to inspect them after
+/// the retry has consumed the backoff.
+///
+/// # Example
+/// ```
+/// # use tryagain::*;
+/// # use std::time::Duration;
+/// use tryagain::test_util::RecordingBackoff;
+///
+/// let backoff = RecordingBackoff::new(ConstantBackoff::from_millis(1));
+/// let result = tryagain::retry_if(backoff.clone(), || Err::<(), _>(()), |_, iterations| iterations < 2);
+///
+/// assert_eq!(backoff.records(), [(0, Duration::from_millis(1)), (1, Duration::from_millis(1))]);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct RecordingBackoff<T: Backoff> {
+    inner: T,
+    records: Arc<Mutex<Vec<(u32, Duration)>>>,
+}
+
+#[cfg(feature = "std")]
+impl<T: Backoff> RecordingBackoff<T> {
+    /// Creates a [RecordingBackoff](crate::test_util::RecordingBackoff) that records the delays of `inner`.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            records: Arc::default(),
+        }
+    }
+
+    /// Returns the iterations and delays the backoff returned, in order.
+    pub fn records(&self) -> Vec<(u32, Duration)> {
+        self.records.lock().unwrap().clone()
+    }
+
+    fn record(&self, iterations: u32, duration: Duration) -> Duration {
+        self.records.lock().unwrap().push((iterations, duration));
+        duration
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Backoff> Backoff for RecordingBackoff<T> {
+    fn backoff_period(&mut self, iterations: u32) -> Duration {
+        let duration = self.inner.backoff_period(iterations);
+        self.record(iterations, duration)
+    }
+
+    fn compute(&mut self, ctx: &BackoffContext) -> Duration {
+        let duration = self.inner.compute(ctx);
+        self.record(ctx.iterations, duration)
+    }
+
+    fn next_backoff(&mut self, ctx: &BackoffContext) -> Option<Duration> {
+        let duration = self.inner.next_backoff(ctx)?;
+        Some(self.record(ctx.iterations, duration))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_iterations() {
+        let mut backoff = MockBackoff::new();
+        let result = crate::retry_if(&mut backoff, || Err::<(), _>(()), |_, iterations| iterations < 3);
+
+        assert_eq!(result, Err(()));
+        assert_eq!(backoff.calls(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_recording() {
+        let backoff = RecordingBackoff::new(crate::LinearBackoff::new(Duration::from_millis(1)));
+        let result = crate::retry_if(backoff.clone(), || Err::<(), _>(()), |_, iterations| iterations < 3);
+
+        assert_eq!(result, Err(()));
+        assert_eq!(
+            backoff.records(),
+            [(0, Duration::ZERO), (1, Duration::from_millis(1)), (2, Duration::from_millis(2))]
+        );
+    }
+
+    #[cfg(feature = "runtime-tokio")]
+    #[tokio::test]
+    async fn test_recording_future() {
+        let backoff = RecordingBackoff::new(crate::LinearBackoff::new(Duration::from_millis(1)));
+        let result = crate::future::retry_if(
+            backoff.clone(),
+            || async { Err::<(), _>(()) },
+            |_, iterations| iterations < 3,
+        )
+        .await;
+
+        assert_eq!(result, Err(()));
+        assert_eq!(backoff.records(), [(1, Duration::from_millis(1)), (2, Duration::from_millis(2))]);
+    }
+}
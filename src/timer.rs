@@ -0,0 +1,49 @@
+//! The timers used by [RetryFuture](crate::future::RetryFuture) to wait out a
+//! backoff period on each of the supported runtimes.
+
+use std::{future::Future, pin::Pin, time::Duration};
+
+/// A future that resolves once a backoff period has elapsed.
+#[cfg(not(all(feature = "runtime-wasm", target_arch = "wasm32")))]
+pub(crate) type Timer = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A future that resolves once a backoff period has elapsed. WASM futures
+/// aren't `Send`, so neither is this timer.
+#[cfg(all(feature = "runtime-wasm", target_arch = "wasm32"))]
+pub(crate) type Timer = Pin<Box<dyn Future<Output = ()>>>;
+
+#[cfg(all(
+    feature = "runtime-tokio",
+    not(all(feature = "runtime-wasm", target_arch = "wasm32"))
+))]
+pub(crate) fn sleep(duration: Duration) -> Timer {
+    Box::pin(tokio::time::sleep(duration))
+}
+
+#[cfg(all(
+    feature = "runtime-async-std",
+    not(feature = "runtime-tokio"),
+    not(all(feature = "runtime-wasm", target_arch = "wasm32"))
+))]
+pub(crate) fn sleep(duration: Duration) -> Timer {
+    Box::pin(async_std::task::sleep(duration))
+}
+
+#[cfg(all(
+    feature = "runtime-smol",
+    not(any(feature = "runtime-tokio", feature = "runtime-async-std")),
+    not(all(feature = "runtime-wasm", target_arch = "wasm32"))
+))]
+pub(crate) fn sleep(duration: Duration) -> Timer {
+    let timer = async_io::Timer::after(duration);
+    Box::pin(async move {
+        timer.await;
+    })
+}
+
+/// In the browser the other runtimes' timers don't work, so `runtime-wasm`
+/// takes precedence there.
+#[cfg(all(feature = "runtime-wasm", target_arch = "wasm32"))]
+pub(crate) fn sleep(duration: Duration) -> Timer {
+    Box::pin(gloo_timers::future::sleep(duration))
+}
@@ -0,0 +1,162 @@
+//! Instrumentation emitted by the retry functions when the `tracing` or
+//! `metrics` features are enabled. Without the features these functions
+//! compile to nothing.
+//!
+//! Metrics are labeled with the `operation` name when the retry was given one.
+
+use core::time::Duration;
+
+/// Records that an attempt completed, successfully or not.
+#[inline]
+pub(crate) fn attempted(name: Option<&'static str>) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("tryagain.attempts", labels(name)).increment(1);
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = name;
+}
+
+/// Records that an attempt failed and will be retried after `duration`.
+#[inline]
+pub(crate) fn retrying(name: Option<&'static str>, iterations: u32, duration: Duration) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(iterations, ?duration, "attempt failed, retrying");
+
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!("tryagain.retries", labels(name)).increment(1);
+        metrics::histogram!("tryagain.backoff", labels(name)).record(duration);
+    }
+
+    #[cfg(not(any(feature = "tracing", feature = "metrics")))]
+    let _ = (name, iterations, duration);
+    #[cfg(all(feature = "tracing", not(feature = "metrics")))]
+    let _ = name;
+    #[cfg(all(feature = "metrics", not(feature = "tracing")))]
+    let _ = iterations;
+}
+
+/// Records that an attempt failed and won't be retried.
+#[inline]
+pub(crate) fn gave_up(name: Option<&'static str>) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("tryagain.give_ups", labels(name)).increment(1);
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = name;
+}
+
+#[cfg(feature = "metrics")]
+fn labels(name: Option<&'static str>) -> alloc::vec::Vec<metrics::Label> {
+    name.map(|name| metrics::Label::new("operation", name))
+        .into_iter()
+        .collect()
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use crate::{retry_if_hinted, retry_if_named, retry_with_history, HintedBackoff, ImmediateBackoff};
+    use metrics_util::{
+        debugging::{DebugValue, DebuggingRecorder, Snapshotter},
+        CompositeKey,
+    };
+    use std::cell::Cell;
+
+    fn snapshot(snapshotter: &Snapshotter) -> Vec<(CompositeKey, DebugValue)> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key, value))
+            .collect()
+    }
+
+    fn counter(snapshot: &[(CompositeKey, DebugValue)], name: &str) -> u64 {
+        snapshot
+            .iter()
+            .find_map(|(key, value)| match value {
+                DebugValue::Counter(count) if key.key().name() == name => {
+                    assert!(key.key().labels().any(|label| label.value() == "fetch"));
+                    Some(*count)
+                }
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    fn unlabeled_counter(snapshot: &[(CompositeKey, DebugValue)], name: &str) -> u64 {
+        snapshot
+            .iter()
+            .find_map(|(key, value)| match value {
+                DebugValue::Counter(count) if key.key().name() == name && key.key().labels().next().is_none() => {
+                    Some(*count)
+                }
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let attempts = Cell::new(0);
+
+        metrics::with_local_recorder(&recorder, || {
+            let result = retry_if_named(
+                "fetch",
+                ImmediateBackoff,
+                || {
+                    attempts.set(attempts.get() + 1);
+
+                    if attempts.get() < 3 {
+                        Err(())
+                    } else {
+                        Ok(())
+                    }
+                },
+                |_, _| true,
+            );
+            assert_eq!(result, Ok(()));
+
+            let result = retry_if_named("fetch", ImmediateBackoff, || Err::<(), _>(()), |_, iterations| iterations < 1);
+            assert_eq!(result, Err(()));
+        });
+
+        let snapshot = snapshot(&snapshotter);
+        assert_eq!(counter(&snapshot, "tryagain.attempts"), 5);
+        assert_eq!(counter(&snapshot, "tryagain.retries"), 3);
+        assert_eq!(counter(&snapshot, "tryagain.give_ups"), 1);
+    }
+
+    #[test]
+    fn test_metrics_outside_retry_loop() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let attempts = Cell::new(0);
+
+        metrics::with_local_recorder(&recorder, || {
+            let (value, history) = retry_with_history(ImmediateBackoff, || {
+                attempts.set(attempts.get() + 1);
+
+                if attempts.get() < 3 {
+                    Err(attempts.get())
+                } else {
+                    Ok(attempts.get())
+                }
+            });
+            assert_eq!(value, 3);
+            assert_eq!(history, [1, 2]);
+
+            let backoff = HintedBackoff::new(ImmediateBackoff, |_: &()| None);
+            let result = retry_if_hinted(backoff, || Err::<(), _>(()), |_, iterations| iterations < 1);
+            assert_eq!(result, Err(()));
+        });
+
+        // Every retry follows an attempt, so the counters stay consistent.
+        let snapshot = snapshot(&snapshotter);
+        assert_eq!(unlabeled_counter(&snapshot, "tryagain.attempts"), 5);
+        assert_eq!(unlabeled_counter(&snapshot, "tryagain.retries"), 3);
+        assert_eq!(unlabeled_counter(&snapshot, "tryagain.give_ups"), 1);
+    }
+}